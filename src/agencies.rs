@@ -0,0 +1,297 @@
+use serde::{ Deserialize, Serialize };
+
+use crate::geography::state_name;
+
+/// The government agency columns of the spreadsheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AgencyColumn {
+    StateAgency,
+    StateSubAgency,
+    FederalLegislative,
+    FederalExecutiveDepartment,
+    FederalExecutiveSubAgency,
+    FederalIndependent,
+    FederalBoard,
+    FederalQuasiOfficial,
+    FederalCourt,
+}
+
+/// An entry of the agency table in the config file.
+///
+/// ```toml
+/// [[agencies]]
+/// heading = "Arizona. Dept. of Health Services"
+/// column = "state-agency"
+/// name = "Arizona Department of Health Services"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct Agency {
+    /// The corporate name as it appears in 110 or 710, units separated by `. `.
+    ///
+    /// Headings that start with it match too, e.g. `Arizona. Dept. of Health Services. Division of Licensing`.
+    pub heading: String,
+    pub column: AgencyColumn,
+    /// The name written to the column, the heading when left out.
+    pub name: Option<String>,
+}
+
+/// What 008/28 says about the level of government that issued the periodical.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GovernmentLevel {
+    State,
+    Federal,
+    /// Local, multi-state, international, or unspecified.
+    Other,
+}
+
+impl GovernmentLevel {
+    /// Reads an 008/28 government publication code, `None` if it isn't a government publication or
+    /// it's unknown whether it is (`u`, or `|` when not coded).
+    pub fn from_code(code: char) -> Option<Self> {
+        match code {
+            's' => Some(Self::State),
+            'f' => Some(Self::Federal),
+            'a' | 'c' | 'i' | 'l' | 'm' | 'o' | 'z' => Some(Self::Other),
+            _ => None,
+        }
+    }
+}
+
+/// The issuing bodies of a government publication, by column. Each is pipe-joined when written.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GovernmentAgencies {
+    #[serde(rename = "State Agency")]
+    pub state_agencies: Vec<String>,
+    #[serde(rename = "State Sub-Agency")]
+    pub state_sub_agencies: Vec<String>,
+    #[serde(rename = "Federal Legislative Branch Agency")]
+    pub federal_legislative: Vec<String>,
+    #[serde(rename = "Federal Executive Department")]
+    pub federal_executive_departments: Vec<String>,
+    #[serde(rename = "Federal Executive Department Sub-Agency or Bureau")]
+    pub federal_executive_sub_agencies: Vec<String>,
+    #[serde(rename = "Federal Independent Agency")]
+    pub federal_independent: Vec<String>,
+    #[serde(rename = "Federal Board, Commission, or Committee")]
+    pub federal_boards: Vec<String>,
+    #[serde(rename = "Federal Quasi-Official Agency")]
+    pub federal_quasi_official: Vec<String>,
+    #[serde(rename = "Federal Court or Judicial Agency")]
+    pub federal_courts: Vec<String>,
+}
+
+impl GovernmentAgencies {
+    /// Sorts the corporate names of a government publication into the agency columns.
+    ///
+    /// `names` are the units of each 110 and 710, jurisdiction first, e.g. `["Arizona", "Dept. of Health"]`.
+    /// Names in the agency table are placed as configured. Otherwise, names under a state or the
+    /// United States are placed by their wording and noted for review, and anything else is left out.
+    pub fn from_names(names: &[Vec<String>], level: Option<GovernmentLevel>, table: &[Agency]) -> (Self, Vec<String>) {
+        let mut agencies = GovernmentAgencies::default();
+        let mut notes = Vec::new();
+
+        for units in names {
+            let heading = join_units(units);
+            let matches = table
+                .iter()
+                .filter_map(|agency| heading_prefix_len(&agency.heading, units).map(|n| (agency, n)))
+                .collect::<Vec<_>>();
+            if let Some((deepest, n)) = matches.iter().max_by_key(|(_, n)| *n) {
+                for (agency, _) in &matches {
+                    let name = agency.name.clone().unwrap_or_else(|| agency.heading.clone());
+                    agencies.push(agency.column, name);
+                }
+                // units under the deepest match that aren't in the table, e.g. a division
+                let sub_column = match deepest.column {
+                    AgencyColumn::StateAgency => Some(AgencyColumn::StateSubAgency),
+                    AgencyColumn::FederalExecutiveDepartment => Some(AgencyColumn::FederalExecutiveSubAgency),
+                    _ => None,
+                };
+                if let Some(sub_column) = sub_column.filter(|_| units.len() > *n) {
+                    agencies.push(sub_column, join_units(&units[*n..]));
+                }
+                continue;
+            }
+
+            let Some(jurisdiction) = units.first() else {
+                continue;
+            };
+            // `U.S.` keeps its period, since the `S` is an initial
+            let is_federal = matches!(jurisdiction.trim_end_matches('.'), "United States" | "U.S");
+            let is_state = state_name(jurisdiction).is_some();
+            if is_federal && level == Some(GovernmentLevel::State) || is_state && level == Some(GovernmentLevel::Federal) {
+                notes.push(format!("\"{}\" disagrees with the government level in 008/28.", heading));
+            }
+
+            if is_state && units.len() > 1 {
+                agencies.push(AgencyColumn::StateAgency, join_units(&units[..2]));
+                if units.len() > 2 {
+                    agencies.push(AgencyColumn::StateSubAgency, join_units(&units[2..]));
+                }
+            } else if is_federal && units.len() > 1 {
+                let column = federal_column(&units[1]);
+                agencies.push(column, join_units(&units[..2]));
+                if units.len() > 2 {
+                    // only executive departments have a sub-agency column
+                    let sub_column = match column {
+                        AgencyColumn::FederalExecutiveDepartment => AgencyColumn::FederalExecutiveSubAgency,
+                        column => column,
+                    };
+                    agencies.push(sub_column, join_units(&units[2..]));
+                }
+            } else {
+                continue;
+            }
+            notes.push(format!("\"{}\" is not in the agency table, check its agency columns.", heading));
+        }
+
+        (agencies, notes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        [
+            &self.state_agencies,
+            &self.state_sub_agencies,
+            &self.federal_legislative,
+            &self.federal_executive_departments,
+            &self.federal_executive_sub_agencies,
+            &self.federal_independent,
+            &self.federal_boards,
+            &self.federal_quasi_official,
+            &self.federal_courts,
+        ]
+            .iter()
+            .all(|column| column.is_empty())
+    }
+
+    fn push(&mut self, column: AgencyColumn, name: String) {
+        let values = match column {
+            AgencyColumn::StateAgency => &mut self.state_agencies,
+            AgencyColumn::StateSubAgency => &mut self.state_sub_agencies,
+            AgencyColumn::FederalLegislative => &mut self.federal_legislative,
+            AgencyColumn::FederalExecutiveDepartment => &mut self.federal_executive_departments,
+            AgencyColumn::FederalExecutiveSubAgency => &mut self.federal_executive_sub_agencies,
+            AgencyColumn::FederalIndependent => &mut self.federal_independent,
+            AgencyColumn::FederalBoard => &mut self.federal_boards,
+            AgencyColumn::FederalQuasiOfficial => &mut self.federal_quasi_official,
+            AgencyColumn::FederalCourt => &mut self.federal_courts,
+        };
+        if !values.contains(&name) {
+            values.push(name);
+        }
+    }
+}
+
+/// Joins the units of a corporate name with periods, e.g. `Arizona. Dept. of Health`.
+///
+/// Units that end in a period already, like `U.S.`, aren't given a second one.
+fn join_units(units: &[String]) -> String {
+    units.iter().fold(String::new(), |mut heading, unit| {
+        if !heading.is_empty() {
+            heading.push_str(if heading.ends_with('.') { " " } else { ". " });
+        }
+        heading.push_str(unit);
+        heading
+    })
+}
+
+/// How many units of a corporate name the table heading covers, if it's the whole name or its first units.
+fn heading_prefix_len(heading: &str, units: &[String]) -> Option<usize> {
+    let heading = heading.trim().trim_end_matches('.').to_lowercase();
+    (1..=units.len()).find(|n| join_units(&units[..*n]).to_lowercase() == heading)
+}
+
+/// Guesses the federal column from the first unit under `United States`.
+fn federal_column(unit: &str) -> AgencyColumn {
+    let words = unit.split_whitespace().collect::<Vec<_>>();
+    let has_word = |word: &str| words.iter().any(|w| w.trim_end_matches([',', '.']) == word);
+    if ["Congress", "Senate", "House", "Library of Congress", "Government Printing Office"]
+        .iter()
+        .any(|name| unit.starts_with(name))
+    {
+        AgencyColumn::FederalLegislative
+    } else if unit.starts_with("Dept.") || unit.starts_with("Department") {
+        AgencyColumn::FederalExecutiveDepartment
+    } else if has_word("Court") || has_word("Courts") {
+        AgencyColumn::FederalCourt
+    } else if ["Board", "Commission", "Committee", "Council"].iter().any(|word| has_word(word)) {
+        AgencyColumn::FederalBoard
+    } else {
+        AgencyColumn::FederalIndependent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn units(units: &[&str]) -> Vec<String> {
+        units.iter().map(|unit| unit.to_string()).collect()
+    }
+
+    #[test]
+    fn reads_government_publication_codes() {
+        assert_eq!(GovernmentLevel::from_code('s'), Some(GovernmentLevel::State));
+        assert_eq!(GovernmentLevel::from_code('f'), Some(GovernmentLevel::Federal));
+        for code in ['a', 'c', 'i', 'l', 'm', 'o', 'z'] {
+            assert_eq!(GovernmentLevel::from_code(code), Some(GovernmentLevel::Other), "{}", code);
+        }
+        // not a government publication, unknown, and not coded
+        for code in [' ', '#', 'u', '|'] {
+            assert_eq!(GovernmentLevel::from_code(code), None, "{:?}", code);
+        }
+    }
+
+    #[test]
+    fn matches_the_agency_table_by_heading_prefix() {
+        let table = [
+            Agency {
+                heading: "Arizona. Dept. of Health Services.".to_string(),
+                column: AgencyColumn::StateAgency,
+                name: Some("Arizona Department of Health Services".to_string()),
+            },
+            Agency {
+                heading: "United States. National Park Service".to_string(),
+                column: AgencyColumn::FederalExecutiveSubAgency,
+                name: None,
+            },
+        ];
+        let names = [
+            units(&["Arizona", "Dept. of Health Services", "Division of Licensing"]),
+            units(&["United States", "National Park Service"]),
+            // only the first units of the heading, so no match
+            units(&["Arizona", "Dept. of Health"]),
+        ];
+        let (agencies, notes) = GovernmentAgencies::from_names(&names, Some(GovernmentLevel::State), &table);
+        assert_eq!(agencies.state_agencies, ["Arizona Department of Health Services", "Arizona. Dept. of Health"]);
+        assert_eq!(agencies.state_sub_agencies, ["Division of Licensing"]);
+        assert_eq!(agencies.federal_executive_sub_agencies, ["United States. National Park Service"]);
+        // table matches aren't noted, only the name placed by its wording
+        assert_eq!(notes, ["\"Arizona. Dept. of Health\" is not in the agency table, check its agency columns."]);
+    }
+
+    #[test]
+    fn notes_names_that_disagree_with_008() {
+        let names = [units(&["Arizona", "Dept. of Mines and Mineral Resources"])];
+        let (_, notes) = GovernmentAgencies::from_names(&names, Some(GovernmentLevel::Federal), &[]);
+        assert!(notes.contains(
+            &"\"Arizona. Dept. of Mines and Mineral Resources\" disagrees with the government level in 008/28.".to_string()
+        ));
+
+        let names = [units(&["United States", "Bureau of Reclamation"])];
+        let (agencies, notes) = GovernmentAgencies::from_names(&names, Some(GovernmentLevel::State), &[]);
+        assert_eq!(agencies.federal_independent, ["United States. Bureau of Reclamation"]);
+        assert!(notes.iter().any(|note| note.contains("disagrees with the government level")));
+
+        let (_, notes) = GovernmentAgencies::from_names(&names, Some(GovernmentLevel::Federal), &[]);
+        assert!(!notes.iter().any(|note| note.contains("disagrees")));
+    }
+
+    #[test]
+    fn abbreviated_united_states_is_federal() {
+        let names = vec![vec!["U.S.".to_string(), "Dept. of Agriculture".to_string()]];
+        let (agencies, _) = GovernmentAgencies::from_names(&names, Some(GovernmentLevel::Federal), &[]);
+        assert_eq!(agencies.federal_executive_departments, ["U.S. Dept. of Agriculture"]);
+    }
+}
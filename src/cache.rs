@@ -0,0 +1,168 @@
+use std::path::{ Path, PathBuf };
+
+use robo_archiver::ArchiveError;
+use serde::{ Deserialize, Serialize };
+
+use crate::{ marc::Record, titles::is_similar_title, CallNumber };
+
+/// A MARC record from an earlier run, with what the operator gave for it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedRecord {
+    /// What the record is cached by. Records without an OCLC number aren't cached.
+    pub oclc_number: String,
+    /// The file name title it was given for, then its 245 and 246 titles.
+    pub titles: Vec<String>,
+    /// Its OCLC number, ISSN, and LCCN, e.g. `issn:0028-0836`, to find it when the titles differ.
+    #[serde(default)]
+    pub match_keys: Vec<String>,
+    /// The record's fields, as they were read from the paste, file, or catalog.
+    pub marc: Vec<Record>,
+    pub call_number: CallNumber,
+    pub description: String,
+    pub topics: Vec<String>,
+}
+
+/// A directory of cached records, one JSON file per OCLC number, e.g. `000012345.json`.
+pub struct MarcCache {
+    dir: PathBuf,
+}
+
+impl MarcCache {
+    /// The cache in `dir`, or `None` when caching is turned off with an empty `cache_dir`.
+    pub fn open(dir: &str) -> Option<Self> {
+        if dir.trim().is_empty() {
+            None
+        } else {
+            Some(Self { dir: PathBuf::from(dir) })
+        }
+    }
+
+    /// The first cached record with a title like `title`.
+    pub fn find(&self, title: &str) -> Option<CachedRecord> {
+        self.records().find(|record| record.titles.iter().any(|cached| is_similar_title(title, cached)))
+    }
+
+    /// The first cached record with any of the match keys, e.g. one cached under another file name title.
+    pub fn find_by_keys(&self, keys: &[String]) -> Option<CachedRecord> {
+        self.records().find(|record| record.match_keys.iter().any(|key| keys.contains(key)))
+    }
+
+    /// The readable records, in file name order.
+    fn records(&self) -> impl Iterator<Item = CachedRecord> {
+        // no directory yet is an empty cache
+        let mut paths = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+            .into_iter()
+            .filter_map(|path| match read_record(&path) {
+                Ok(record) => Some(record),
+                Err(err) => {
+                    eprintln!("[WARN] Could not read the cached record {:?}: {}", path, err);
+                    None
+                }
+            })
+    }
+
+    /// Saves a record, replacing any earlier one with the same OCLC number.
+    pub fn save(&self, record: &CachedRecord) -> Result<(), ArchiveError> {
+        if record.oclc_number.is_empty() {
+            return Err(ArchiveError::InvalidMarc("the record has no OCLC number to cache it by".to_string()));
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        let file = std::fs::File::create(self.dir.join(format!("{}.json", record.oclc_number)))?;
+        serde_json::to_writer_pretty(file, record)?;
+        Ok(())
+    }
+}
+
+fn read_record(path: &Path) -> Result<CachedRecord, ArchiveError> {
+    let file = std::fs::File::open(path)?;
+    Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marc::read_marc;
+
+    fn record(oclc_number: &str, title: &str, match_keys: &[&str]) -> CachedRecord {
+        CachedRecord {
+            oclc_number: oclc_number.to_string(),
+            titles: vec![title.to_string()],
+            match_keys: match_keys.iter().map(|key| key.to_string()).collect(),
+            marc: Vec::new(),
+            call_number: CallNumber::Periodical,
+            description: String::new(),
+            topics: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn saves_and_finds_records_by_oclc_number_and_title() {
+        let dir = std::env::temp_dir().join(format!("robo-archiver-cache-round-trip-{}", std::process::id()));
+        let cache = MarcCache::open(dir.to_str().unwrap()).unwrap();
+        let mut saved = record("000012345", "Arizona Highways", &["oclc:000012345"]);
+        saved.marc = read_marc(b"001\t\tocm00012345\n245\t00\t$aArizona highways.").unwrap();
+        saved.call_number = CallNumber::Shelf("HE5633.A6 A75".to_string());
+        saved.description = "A magazine of the state's scenery.".to_string();
+        saved.topics = vec!["Tourism".to_string()];
+        cache.save(&saved).unwrap();
+        // saving it again replaces it
+        cache.save(&saved).unwrap();
+
+        let files = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(files, ["000012345.json"]);
+
+        let found = cache.find("Arizona highways.").unwrap();
+        assert_eq!(found.oclc_number, "000012345");
+        assert_eq!(found.marc.len(), 2);
+        assert!(matches!(found.call_number, CallNumber::Shelf(ref shelf) if shelf == "HE5633.A6 A75"));
+        assert_eq!(found.description, "A magazine of the state's scenery.");
+        assert_eq!(found.topics, ["Tourism"]);
+        assert!(cache.find("The Desert Magazine").is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn an_empty_cache_dir_turns_caching_off() {
+        assert!(MarcCache::open("").is_none());
+        assert!(MarcCache::open("  ").is_none());
+        // a directory that doesn't exist yet is an empty cache
+        let dir = std::env::temp_dir().join(format!("robo-archiver-cache-missing-{}", std::process::id()));
+        assert!(MarcCache::open(dir.to_str().unwrap()).unwrap().find("Arizona Highways").is_none());
+    }
+
+    #[test]
+    fn records_without_an_oclc_number_are_not_saved() {
+        let dir = std::env::temp_dir().join(format!("robo-archiver-cache-untitled-{}", std::process::id()));
+        let cache = MarcCache::open(dir.to_str().unwrap()).unwrap();
+        assert!(cache.save(&record("", "", &[])).is_err());
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn finds_records_by_match_key() {
+        let dir = std::env::temp_dir().join(format!("robo-archiver-cache-{}", std::process::id()));
+        let cache = MarcCache::open(dir.to_str().unwrap()).unwrap();
+        cache.save(&record("000012345", "Arizona Highways", &["oclc:000012345", "issn:0004-1521"])).unwrap();
+        cache.save(&record("000054321", "The Desert Magazine", &["lccn:sn85001234"])).unwrap();
+
+        let found = cache.find_by_keys(&["issn:0004-1521".to_string()]);
+        assert_eq!(found.map(|record| record.oclc_number), Some("000012345".to_string()));
+        let found = cache.find_by_keys(&["issn:0028-0836".to_string(), "lccn:sn85001234".to_string()]);
+        assert_eq!(found.map(|record| record.titles), Some(vec!["The Desert Magazine".to_string()]));
+        assert!(cache.find_by_keys(&["oclc:000067890".to_string()]).is_none());
+        assert!(cache.find_by_keys(&[]).is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,486 @@
+use robo_archiver::ArchiveError;
+use serde::Deserialize;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{ identifiers::{ normalize_issn, normalize_lccn }, marc::{ Record, Subfield } };
+
+const MARCXML_NAMESPACE: &str = "http://www.loc.gov/MARC21/slim";
+
+/// What to look a record up by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogQuery {
+    OclcNumber(String),
+    Issn(String),
+    Lccn(String),
+    Title(String),
+}
+
+impl CatalogQuery {
+    /// Reads what the operator typed, e.g. `893691141` or `ocn893691141` is an OCLC number, `0028-0836` an
+    /// ISSN, `sn 85-1234` an LCCN, and anything else a title.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if input.is_empty() {
+            return None;
+        }
+        let looks_like_issn = input.contains('-') || input.ends_with(['X', 'x']);
+        if let (true, Some(issn)) = (looks_like_issn, normalize_issn(input)) {
+            return Some(CatalogQuery::Issn(issn));
+        }
+        let oclc_number = input.trim_start_matches("(OCoLC)");
+        let oclc_number = ["ocm", "ocn", "on"]
+            .iter()
+            .find_map(|prefix| oclc_number.strip_prefix(prefix))
+            .unwrap_or(oclc_number);
+        if !oclc_number.is_empty() && oclc_number.chars().all(|c| c.is_ascii_digit()) {
+            return Some(CatalogQuery::OclcNumber(oclc_number.to_string()));
+        }
+        if let Some(lccn) = normalize_lccn(input) {
+            return Some(CatalogQuery::Lccn(lccn));
+        }
+        Some(CatalogQuery::Title(input.to_string()))
+    }
+}
+
+/// A record found in a catalog.
+#[derive(Debug)]
+pub struct CatalogRecord {
+    /// 245 `$a` and `$b`, to pick the record by.
+    pub title: String,
+    /// The record's fields.
+    pub marc: Vec<Record>,
+}
+
+/// A catalog that can be searched for MARC records.
+///
+/// [`SruCatalog`] is the real one. Anything else that can answer a query, like a stub server or a
+/// fixed list of records, can stand in for it.
+pub trait Catalog {
+    fn search(&self, query: &CatalogQuery) -> Result<Vec<CatalogRecord>, ArchiveError>;
+}
+
+/// What came of searching for what the operator typed.
+#[derive(Debug)]
+pub enum SearchOutcome {
+    /// Nothing was typed, so the record is pasted instead.
+    Skipped,
+    /// The catalog couldn't be searched, so the record is pasted instead.
+    Failed(ArchiveError),
+    /// Nothing was found, so the operator can search again.
+    NotFound(CatalogQuery),
+    Found(Vec<CatalogRecord>),
+}
+
+/// Searches the catalog for what the operator typed, e.g. an OCLC number or a title.
+pub fn search_input(catalog: &dyn Catalog, input: &str) -> SearchOutcome {
+    let Some(query) = CatalogQuery::parse(input) else {
+        return SearchOutcome::Skipped;
+    };
+    match catalog.search(&query) {
+        Ok(records) if records.is_empty() => SearchOutcome::NotFound(query),
+        Ok(records) => SearchOutcome::Found(records),
+        Err(err) => SearchOutcome::Failed(err),
+    }
+}
+
+/// The SRU endpoint of an institution's catalog, from the `[sru]` table of the config file.
+///
+/// The indexes differ between catalogs, e.g. Koha uses `rec.id`, and Alma `alma.other_system_number`
+/// for OCLC numbers, so they can be set along with the URL.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SruConfig {
+    /// The base URL, e.g. `https://catalog.example.org/sru`.
+    pub url: String,
+    pub version: String,
+    pub record_schema: String,
+    pub oclc_index: String,
+    pub issn_index: String,
+    pub lccn_index: String,
+    pub title_index: String,
+    pub maximum_records: usize,
+}
+
+impl Default for SruConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            version: "1.2".to_string(),
+            record_schema: "marcxml".to_string(),
+            oclc_index: "bath.standardIdentifier".to_string(),
+            issn_index: "bath.issn".to_string(),
+            lccn_index: "bath.lccn".to_string(),
+            title_index: "dc.title".to_string(),
+            maximum_records: 10,
+        }
+    }
+}
+
+/// Searches a catalog with SRU `searchRetrieve` requests and reads the MARCXML it returns.
+pub struct SruCatalog {
+    config: SruConfig,
+    agent: ureq::Agent,
+}
+
+impl SruCatalog {
+    pub fn new(config: SruConfig) -> Self {
+        let agent = ureq::AgentBuilder::new().timeout(std::time::Duration::from_secs(30)).build();
+        Self { config, agent }
+    }
+
+    /// The CQL for a query, e.g. `bath.issn="0028-0836"`.
+    fn cql(&self, query: &CatalogQuery) -> String {
+        let (index, value) = match query {
+            CatalogQuery::OclcNumber(number) => (&self.config.oclc_index, number),
+            CatalogQuery::Issn(issn) => (&self.config.issn_index, issn),
+            CatalogQuery::Lccn(lccn) => (&self.config.lccn_index, lccn),
+            CatalogQuery::Title(title) => (&self.config.title_index, title),
+        };
+        format!("{}=\"{}\"", index, value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+impl Catalog for SruCatalog {
+    fn search(&self, query: &CatalogQuery) -> Result<Vec<CatalogRecord>, ArchiveError> {
+        let response = self.agent
+            .get(&self.config.url)
+            .query("operation", "searchRetrieve")
+            .query("version", &self.config.version)
+            .query("query", &self.cql(query))
+            .query("recordSchema", &self.config.record_schema)
+            .query("recordPacking", "xml")
+            .query("maximumRecords", &self.config.maximum_records.to_string())
+            .call()
+            .map_err(|err| ArchiveError::Catalog(err.to_string()))?
+            .into_string()?;
+        parse_sru_response(&response)
+    }
+}
+
+/// Reads the records of an SRU response, or its diagnostic message when the search failed.
+pub fn parse_sru_response(response: &str) -> Result<Vec<CatalogRecord>, ArchiveError> {
+    let document = roxmltree::Document::parse(response)
+        .map_err(|err| ArchiveError::Catalog(format!("the response isn't XML: {}", err)))?;
+    let diagnostic = document
+        .descendants()
+        .find(|node| node.has_tag_name("diagnostic"))
+        .map(|diagnostic| {
+            diagnostic
+                .children()
+                .filter(|node| node.has_tag_name("message") || node.has_tag_name("details"))
+                .filter_map(|node| node.text())
+                .collect::<Vec<_>>()
+                .join(": ")
+        });
+    if let Some(diagnostic) = diagnostic {
+        return Err(ArchiveError::Catalog(diagnostic));
+    }
+    let records = document
+        .descendants()
+        .filter(|node| node.has_tag_name((MARCXML_NAMESPACE, "record")))
+        .map(marcxml_record)
+        .collect();
+    Ok(records)
+}
+
+/// Reads a MARCXML `<record>` into its fields. The leader isn't kept, as nothing is read from it.
+fn marcxml_record(record: roxmltree::Node) -> CatalogRecord {
+    let nfc = |text: &str| text.nfc().collect::<String>();
+    let mut fields = Vec::new();
+    let mut title = Vec::new();
+    for field in record.children().filter(|node| node.is_element()) {
+        let tag = field.attribute("tag").unwrap_or_default();
+        match field.tag_name().name() {
+            "controlfield" => {
+                fields.push(Record::control_field(tag, &nfc(field.text().unwrap_or_default())));
+            }
+            "datafield" => {
+                let ind = format!(
+                    "{}{}",
+                    field.attribute("ind1").unwrap_or(" "),
+                    field.attribute("ind2").unwrap_or(" ")
+                );
+                let subfields = field
+                    .children()
+                    .filter(|node| node.has_tag_name((MARCXML_NAMESPACE, "subfield")))
+                    .filter_map(|subfield| {
+                        let code = subfield.attribute("code")?.chars().next()?;
+                        let value = nfc(subfield.text().unwrap_or_default());
+                        if tag == "245" && (code == 'a' || code == 'b') {
+                            title.push(value.trim().to_string());
+                        }
+                        Some(Subfield::new(code, &value))
+                    })
+                    .collect();
+                fields.push(Record::data_field(tag, &ind, subfields));
+            }
+            _ => {}
+        }
+    }
+    CatalogRecord {
+        title: title.join(" "),
+        marc: fields,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ cell::Cell, io::{ BufRead, BufReader, Write }, net::TcpListener, thread::JoinHandle };
+
+    use super::*;
+    use crate::{ config::Config, data::MarcData, marc::read_marc };
+
+    const SRU_RESPONSE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<searchRetrieveResponse xmlns="http://www.loc.gov/zing/srw/">
+  <version>1.2</version>
+  <numberOfRecords>1</numberOfRecords>
+  <records>
+    <record>
+      <recordSchema>marcxml</recordSchema>
+      <recordPacking>xml</recordPacking>
+      <recordData>
+        <record xmlns="http://www.loc.gov/MARC21/slim">
+          <leader>00000cas a2200000 a 4500</leader>
+          <controlfield tag="001">ocm00012345</controlfield>
+          <controlfield tag="003">OCoLC</controlfield>
+          <datafield tag="022" ind1="0" ind2=" ">
+            <subfield code="a">0028-0836</subfield>
+          </datafield>
+          <datafield tag="245" ind1="0" ind2="3">
+            <subfield code="a">An Arizona desert-ation :</subfield>
+            <subfield code="b">the magazine.</subfield>
+          </datafield>
+          <datafield tag="500" ind1=" " ind2=" ">
+            <subfield code="a">Price: $1.00 ‡ issue.</subfield>
+          </datafield>
+        </record>
+      </recordData>
+    </record>
+  </records>
+</searchRetrieveResponse>"#;
+
+    const SRU_DIAGNOSTIC: &str = r#"<?xml version="1.0"?>
+<searchRetrieveResponse xmlns="http://www.loc.gov/zing/srw/">
+  <diagnostics>
+    <diagnostic xmlns="http://www.loc.gov/zing/srw/diagnostic/">
+      <uri>info:srw/diagnostic/1/16</uri>
+      <message>Unsupported index</message>
+      <details>bath.lccn</details>
+    </diagnostic>
+  </diagnostics>
+</searchRetrieveResponse>"#;
+
+    /// Answers every search the same way, and counts the searches.
+    struct StubCatalog {
+        records: Option<Vec<(&'static str, &'static str)>>,
+        searches: Cell<usize>,
+    }
+
+    impl StubCatalog {
+        fn new(records: Option<Vec<(&'static str, &'static str)>>) -> Self {
+            Self { records, searches: Cell::new(0) }
+        }
+    }
+
+    impl Catalog for StubCatalog {
+        fn search(&self, _query: &CatalogQuery) -> Result<Vec<CatalogRecord>, ArchiveError> {
+            self.searches.set(self.searches.get() + 1);
+            match &self.records {
+                Some(records) => Ok(
+                    records
+                        .iter()
+                        .map(|(title, marc)| CatalogRecord {
+                            title: title.to_string(),
+                            marc: read_marc(marc.as_bytes()).unwrap(),
+                        })
+                        .collect()
+                ),
+                None => Err(ArchiveError::Catalog("connection refused".to_string())),
+            }
+        }
+    }
+
+    #[test]
+    fn parses_queries() {
+        let parse = |input: &str| CatalogQuery::parse(input);
+        assert_eq!(parse("893691141"), Some(CatalogQuery::OclcNumber("893691141".to_string())));
+        assert_eq!(parse("ocn893691141"), Some(CatalogQuery::OclcNumber("893691141".to_string())));
+        assert_eq!(parse("(OCoLC)123"), Some(CatalogQuery::OclcNumber("123".to_string())));
+        assert_eq!(parse("0028-0836"), Some(CatalogQuery::Issn("0028-0836".to_string())));
+        assert_eq!(parse("2434-561x"), Some(CatalogQuery::Issn("2434-561X".to_string())));
+        // the check digit of 1234-567 is 9, so this isn't an ISSN
+        assert_eq!(parse("1234-567X"), Some(CatalogQuery::Title("1234-567X".to_string())));
+        assert_eq!(parse("sn 85-1234"), Some(CatalogQuery::Lccn("sn85001234".to_string())));
+        assert_eq!(parse("  Arizona Highways "), Some(CatalogQuery::Title("Arizona Highways".to_string())));
+        assert_eq!(parse("   "), None);
+    }
+
+    #[test]
+    fn reads_marcxml_records() {
+        let records = parse_sru_response(SRU_RESPONSE).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].title, "An Arizona desert-ation : the magazine.");
+        assert_eq!(
+            records[0].marc,
+            [
+                Record::control_field("001", "ocm00012345"),
+                Record::control_field("003", "OCoLC"),
+                Record::data_field("022", "0 ", vec![Subfield::new('a', "0028-0836")]),
+                Record::data_field(
+                    "245",
+                    "03",
+                    vec![Subfield::new('a', "An Arizona desert-ation :"), Subfield::new('b', "the magazine.")]
+                ),
+                // a `$` or `‡` in the data is not a delimiter
+                Record::data_field("500", "  ", vec![Subfield::new('a', "Price: $1.00 ‡ issue.")]),
+            ]
+        );
+
+        let marc = MarcData::try_from_records(records[0].marc.clone(), &Config::default()).unwrap();
+        assert_eq!(marc.oclc_number, Some("000012345".to_string()));
+        assert_eq!(marc.issn, Some("0028-0836".to_string()));
+        assert_eq!(marc.general_notes, ["Price: $1.00 ‡ issue."]);
+    }
+
+    #[test]
+    fn reads_diagnostics() {
+        match parse_sru_response(SRU_DIAGNOSTIC) {
+            Err(ArchiveError::Catalog(message)) => assert_eq!(message, "Unsupported index: bath.lccn"),
+            other => panic!("expected the diagnostic, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn falls_back_to_pasting() {
+        let catalog = StubCatalog::new(Some(vec![("Arizona Highways", "245\t00\t$aArizona Highways")]));
+        assert!(matches!(search_input(&catalog, ""), SearchOutcome::Skipped));
+        assert_eq!(catalog.searches.get(), 0);
+        match search_input(&catalog, "Arizona Highways") {
+            SearchOutcome::Found(records) => assert_eq!(records[0].title, "Arizona Highways"),
+            other => panic!("expected a record, got {:?}", other),
+        }
+
+        let catalog = StubCatalog::new(Some(Vec::new()));
+        assert!(matches!(
+            search_input(&catalog, "0028-0836"),
+            SearchOutcome::NotFound(CatalogQuery::Issn(_))
+        ));
+
+        let catalog = StubCatalog::new(None);
+        assert!(matches!(search_input(&catalog, "893691141"), SearchOutcome::Failed(ArchiveError::Catalog(_))));
+        assert_eq!(catalog.searches.get(), 1);
+    }
+
+    /// Serves one canned HTTP response per connection, and returns the request lines it was sent.
+    fn serve(responses: Vec<String>) -> (SruCatalog, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/sru", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                // the headers, up to the blank line
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                requests.push(request.trim_end().to_string());
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+        let catalog = SruCatalog::new(SruConfig { url, ..SruConfig::default() });
+        (catalog, server)
+    }
+
+    fn http_response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/xml\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+    }
+
+    /// The decoded query parameters of a request line, e.g. `GET /sru?query=... HTTP/1.1`.
+    fn query_parameters(request: &str) -> Vec<(String, String)> {
+        let decode = |value: &str| {
+            let bytes = value.replace('+', " ").into_bytes();
+            let mut decoded = Vec::new();
+            let mut i = 0;
+            while i < bytes.len() {
+                if bytes[i] == b'%' {
+                    decoded.push(u8::from_str_radix(std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap(), 16).unwrap());
+                    i += 3;
+                } else {
+                    decoded.push(bytes[i]);
+                    i += 1;
+                }
+            }
+            String::from_utf8(decoded).unwrap()
+        };
+        let target = request.split(' ').nth(1).unwrap();
+        let (path, query) = target.split_once('?').unwrap();
+        assert_eq!(path, "/sru");
+        query
+            .split('&')
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap();
+                (decode(name), decode(value))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn searches_an_sru_server() {
+        let no_records = r#"<searchRetrieveResponse xmlns="http://www.loc.gov/zing/srw/">
+  <numberOfRecords>0</numberOfRecords>
+</searchRetrieveResponse>"#;
+        let (catalog, server) = serve(vec![
+            http_response("200 OK", SRU_RESPONSE),
+            http_response("200 OK", no_records),
+            http_response("500 Internal Server Error", "down for maintenance"),
+            http_response("200 OK", SRU_DIAGNOSTIC),
+        ]);
+
+        match search_input(&catalog, "ocm00012345") {
+            SearchOutcome::Found(records) => assert_eq!(records[0].title, "An Arizona desert-ation : the magazine."),
+            other => panic!("expected a record, got {:?}", other),
+        }
+        assert!(matches!(
+            search_input(&catalog, r#"The "Arizona" \ Highways"#),
+            SearchOutcome::NotFound(CatalogQuery::Title(_))
+        ));
+        assert!(matches!(search_input(&catalog, "0028-0836"), SearchOutcome::Failed(ArchiveError::Catalog(_))));
+        match search_input(&catalog, "sn 85-1234") {
+            SearchOutcome::Failed(ArchiveError::Catalog(message)) => assert_eq!(message, "Unsupported index: bath.lccn"),
+            other => panic!("expected the diagnostic, got {:?}", other),
+        }
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 4);
+        let parameters = query_parameters(&requests[0]);
+        let expected = [
+            ("operation", "searchRetrieve"),
+            ("version", "1.2"),
+            ("query", "bath.standardIdentifier=\"00012345\""),
+            ("recordSchema", "marcxml"),
+            ("recordPacking", "xml"),
+            ("maximumRecords", "10"),
+        ];
+        assert_eq!(
+            parameters,
+            expected.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect::<Vec<_>>()
+        );
+        // quotes and backslashes are escaped in CQL
+        let query = |request: &str| {
+            query_parameters(request).into_iter().find(|(name, _)| name == "query").unwrap().1
+        };
+        assert_eq!(query(&requests[1]), r#"dc.title="The \"Arizona\" \\ Highways""#);
+        assert_eq!(query(&requests[2]), r#"bath.issn="0028-0836""#);
+        assert_eq!(query(&requests[3]), r#"bath.lccn="sn85001234""#);
+    }
+}
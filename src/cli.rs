@@ -1,4 +1,4 @@
-use clap::{ command, Parser };
+use clap::{ Parser, ValueEnum };
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -19,12 +19,19 @@ pub struct Args {
     #[arg(short, long)]
     pub out_path: Option<String>,
 
-    /// The languages used in the periodical (optional).
+    /// The languages used in the periodical, when the MARC record doesn't say (optional).
     ///
     /// Defaults to "English".
     #[arg(short, long)]
     pub languages: Option<Vec<String>>,
 
+    /// If provided, the languages given with `--languages` are used for every periodical,
+    /// even when the MARC record lists others (optional).
+    ///
+    /// Defaults to false.
+    #[arg(long)]
+    pub override_languages: bool,
+
     /// If provided, the program will search for files in the directory recursively (optional).
     ///
     /// Defaults to false.
@@ -54,4 +61,54 @@ pub struct Args {
     /// Defaults to "NO COPYRIGHT - UNITED STATES. [..abbreviated..] http://rightsstatements.org/vocab/NoC-US/1.0/"
     #[arg(long)]
     pub rights_statement: Option<String>,
+
+    /// If provided, the text layer of each PDF is searched for volume and issue numbers,
+    /// which are offered as suggestions per issue (optional).
+    ///
+    /// Defaults to false.
+    #[arg(long)]
+    pub inspect_pdfs: bool,
+
+    /// If provided, node titles and the Subcollection are built from the MARC title (245) instead of
+    /// the file names (optional).
+    ///
+    /// Defaults to false.
+    #[arg(long)]
+    pub marc_titles: bool,
+
+    /// What to do with duplicate issues: files with the same title and date, or with identical content (optional).
+    ///
+    /// Defaults to "prompt".
+    #[arg(long, value_enum, default_value_t)]
+    pub duplicates: DuplicatePolicy,
+
+    /// A TOML file with institution-specific settings, such as the call number fields to check (optional).
+    ///
+    /// If not provided, the defaults are used.
+    #[arg(long)]
+    pub config: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum DuplicatePolicy {
+    /// Ask which file to keep for each set of duplicates.
+    #[default]
+    Prompt,
+    /// Keep the first file, in path order.
+    KeepFirst,
+    /// Keep the largest file.
+    KeepLargest,
+    /// Stop without writing anything.
+    Fail,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marc_titles_are_opt_in() {
+        assert!(!Args::try_parse_from(["robo_archiver"]).unwrap().marc_titles);
+        assert!(Args::try_parse_from(["robo_archiver", "--marc-titles"]).unwrap().marc_titles);
+    }
 }
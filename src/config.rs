@@ -0,0 +1,75 @@
+use serde::Deserialize;
+use robo_archiver::ArchiveError;
+
+use crate::{ agencies::Agency, catalog::SruConfig };
+
+/// Settings that differ between institutions, read from the TOML file given with `--config`.
+///
+/// Every setting is optional, anything left out keeps its default.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// MARC tags to propose a call number from, in priority order.
+    ///
+    /// Defaults to the local holdings (852) and local call numbers (099, 090) first, then state
+    /// documents (086), then the Library of Congress call number (050).
+    pub call_number_fields: Vec<String>,
+
+    /// Government agencies and the columns they belong in, matched against 110 and 710.
+    ///
+    /// Agencies left out are placed by their wording, e.g. `United States. Dept. of Agriculture` is a
+    /// Federal Executive Department, and noted for review.
+    pub agencies: Vec<Agency>,
+
+    /// Keep relator terms (`$e`) and codes (`$4`) in creators, e.g. `John Smith, 1900-1980, editor`.
+    pub relator_terms: bool,
+
+    /// The sentences of the proposed description. A sentence is left out when any of its placeholders
+    /// are empty.
+    ///
+    /// Placeholders: `{title}`, `{publisher}`, `{place}`, `{date_span}`, `{summary}` (520), and `{notes}` (500).
+    pub description_template: Vec<String>,
+
+    /// The directory records are cached in, with the call number, description, and topics given for them.
+    ///
+    /// Later runs offer to reuse a cached record when a title matches. An empty path turns caching off.
+    pub cache_dir: String,
+
+    /// The catalog's SRU endpoint, to look records up by OCLC number, ISSN, or title instead of pasting them.
+    pub sru: Option<SruConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            call_number_fields: ["852", "099", "090", "086", "050"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            agencies: Vec::new(),
+            relator_terms: false,
+            description_template: [
+                "{summary}",
+                "{title} was published by {publisher} in {place}.",
+                "These issues cover {date_span}.",
+                "{notes}",
+            ]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            cache_dir: "marc-cache".to_string(),
+            sru: None,
+        }
+    }
+}
+
+/// Reads the config file, or returns the defaults when no path is given.
+pub fn load_config(path: Option<String>) -> Result<Config, ArchiveError> {
+    match path {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path)?;
+            Ok(toml::from_str(&contents)?)
+        }
+        None => Ok(Config::default()),
+    }
+}
@@ -0,0 +1,234 @@
+/// Words that make a phrase a contributor's title, e.g. `managing editor` or `director`.
+const ROLE_WORDS: [&str; 20] = [
+    "artist",
+    "author",
+    "chair",
+    "chairman",
+    "chairperson",
+    "compiler",
+    "coordinator",
+    "designer",
+    "director",
+    "editor",
+    "illustrator",
+    "manager",
+    "photographer",
+    "president",
+    "publisher",
+    "reporter",
+    "secretary",
+    "translator",
+    "treasurer",
+    "writer",
+];
+
+/// Verbs of "by" statements and the titles they give, e.g. `edited by John Smith` -> `(Editor) John Smith`.
+const ROLE_VERBS: [(&str, &str); 8] = [
+    ("edited", "Editor"),
+    ("compiled", "Compiler"),
+    ("illustrated", "Illustrator"),
+    ("photographs", "Photographer"),
+    ("photographed", "Photographer"),
+    ("translated", "Translator"),
+    ("written", "Writer"),
+    ("designed", "Designer"),
+];
+
+/// Words that make a name a corporate body rather than a person.
+const CORPORATE_WORDS: [&str; 16] = [
+    "association",
+    "board",
+    "bureau",
+    "club",
+    "college",
+    "commission",
+    "company",
+    "council",
+    "department",
+    "dept",
+    "division",
+    "inc",
+    "office",
+    "society",
+    "tests",
+    "university",
+];
+
+/// Finds contributors in statements of responsibility and credit notes, as `(title) First Last`.
+///
+/// Handles `C.R. Caryl, director`, `Editor: John Smith`, `Editor, John Smith` (508), and
+/// `edited by John Smith` (245 `$c`). Several people are separated by `;` or `and`.
+pub fn suggest_contributors(statements: &[String]) -> Vec<String> {
+    let mut contributors: Vec<String> = Vec::new();
+    for statement in statements {
+        let statement = statement.trim().trim_matches(['"', '\'', '[', ']', ' ']);
+        for part in statement.split([';', '\n']) {
+            for contributor in parse_credit(part) {
+                if !contributors.contains(&contributor) {
+                    contributors.push(contributor);
+                }
+            }
+        }
+    }
+    contributors
+}
+
+/// Whether a note credits someone with a role rather than describing the periodical, e.g.
+/// `C.R. Caryl, director.` or `Editor: Arizona Dept. of Transportation`.
+pub fn is_credit(note: &str) -> bool {
+    let note = note.trim().trim_matches(['"', '\'', '[', ']', ' ']);
+    note.split([';', '\n']).any(|part| matches!(split_credit(part), Some((Some(_), _))))
+}
+
+/// Reads one credit, e.g. `C.R. Caryl, director.`, into zero or more contributors.
+fn parse_credit(credit: &str) -> Vec<String> {
+    let Some((title, names)) = split_credit(credit) else {
+        return Vec::new();
+    };
+    names
+        .into_iter()
+        .filter_map(|name| contributor(title.as_deref(), name))
+        .collect()
+}
+
+/// Splits a credit into its title, if any, and the names it credits. `None` when it isn't shaped like a credit.
+fn split_credit(credit: &str) -> Option<(Option<String>, Vec<&str>)> {
+    let credit = credit.trim().trim_end_matches(['.', ',', ' ', '"']).trim_start_matches('"');
+
+    // `edited by John Smith and Jane Doe`
+    if let Some((before, names)) = credit.split_once(" by ") {
+        let verb = before.split_whitespace().last().unwrap_or_default().to_lowercase();
+        let title = ROLE_VERBS
+            .iter()
+            .find(|(v, _)| *v == verb)
+            .map(|(_, title)| title.to_string());
+        return Some((title, split_names(names)));
+    }
+
+    // `Editor: John Smith`
+    if let Some((role, names)) = credit.split_once(": ") {
+        if is_role(role) {
+            return Some((Some(title_case(role)), split_names(names)));
+        }
+    }
+
+    // `C.R. Caryl, director` or `Editor, John Smith`
+    if let Some((first, second)) = credit.rsplit_once(", ") {
+        if is_role(second) {
+            return Some((Some(title_case(second)), vec![first]));
+        }
+        if is_role(first) {
+            return Some((Some(title_case(first)), split_names(second)));
+        }
+    }
+
+    None
+}
+
+/// Formats a contributor if the name looks like a person, e.g. `(Director) C.R. Caryl`.
+fn contributor(title: Option<&str>, name: &str) -> Option<String> {
+    let name = name.trim().trim_end_matches([',', ' ']);
+    if !is_personal_name(name) {
+        return None;
+    }
+    match title {
+        Some(title) => Some(format!("({}) {}", title, name)),
+        None => Some(name.to_string()),
+    }
+}
+
+fn split_names(names: &str) -> Vec<&str> {
+    names
+        .split(" and ")
+        .flat_map(|names| names.split(" & "))
+        .flat_map(|names| names.split(", "))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// A role is a short lowercase or title case phrase ending in a role word, e.g. `managing editor`.
+fn is_role(phrase: &str) -> bool {
+    let words = phrase.split_whitespace().collect::<Vec<_>>();
+    let Some(last) = words.last() else {
+        return false;
+    };
+    // one plural `s`, e.g. `editors`
+    let last = last.trim_end_matches(['.', ',']).to_lowercase();
+    let singular = last.strip_suffix('s').unwrap_or(&last);
+    words.len() <= 3 && (ROLE_WORDS.contains(&last.as_str()) || ROLE_WORDS.contains(&singular))
+}
+
+/// Two to four capitalized words or initials, none of them corporate, e.g. `C.R. Caryl` or `John A. Smith`.
+fn is_personal_name(name: &str) -> bool {
+    let words = name.split_whitespace().collect::<Vec<_>>();
+    let has_surname = words.len() >= 2 || words.first().is_some_and(|w| w.contains('.') && w.len() > 4);
+    has_surname &&
+        words.len() <= 4 &&
+        words.iter().all(|word| {
+            word.chars().next().is_some_and(|c| c.is_uppercase()) &&
+                !CORPORATE_WORDS.contains(&word.trim_end_matches(['.', ',']).to_lowercase().as_str())
+        })
+}
+
+/// E.g. `managing editor` -> `Managing Editor`.
+fn title_case(phrase: &str) -> String {
+    phrase
+        .split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggest(statements: &[&str]) -> Vec<String> {
+        suggest_contributors(&statements.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn reads_names_before_or_after_a_role() {
+        assert_eq!(suggest(&["C.R. Caryl, director."]), ["(Director) C.R. Caryl"]);
+        assert_eq!(suggest(&["Editor: John Smith"]), ["(Editor) John Smith"]);
+        assert_eq!(suggest(&["Managing editor, Jane A. Doe"]), ["(Managing Editor) Jane A. Doe"]);
+        assert_eq!(suggest(&["Editors: John Smith and Jane Doe."]), ["(Editors) John Smith", "(Editors) Jane Doe"]);
+    }
+
+    #[test]
+    fn reads_by_statements() {
+        assert_eq!(suggest(&["edited by John Smith and Jane Doe."]), ["(Editor) John Smith", "(Editor) Jane Doe"]);
+        assert_eq!(suggest(&["photographs by Ansel Adams ; written by C.R. Caryl"]), [
+            "(Photographer) Ansel Adams",
+            "(Writer) C.R. Caryl",
+        ]);
+        assert_eq!(suggest(&["Issued by John Smith"]), ["John Smith"]);
+    }
+
+    #[test]
+    fn strips_only_one_plural_s() {
+        assert!(is_role("editors"));
+        assert!(is_role("Director."));
+        assert!(!is_role("editorss"));
+        assert!(!is_role("the editor of these pages"));
+        assert!(suggest(&["Editorss: John Smith"]).is_empty());
+    }
+
+    #[test]
+    fn rejects_corporate_names() {
+        assert!(suggest(&["Arizona Dept. of Transportation, publisher."]).is_empty());
+        assert!(suggest(&["edited by the Arizona Historical Society"]).is_empty());
+        assert!(suggest(&["Editor: Public Affairs Office"]).is_empty());
+        assert!(suggest(&["Includes an annual index."]).is_empty());
+        // credits are still credits when no person is named
+        assert!(is_credit("Arizona Dept. of Transportation, publisher."));
+        assert!(!is_credit("Some issues are in Spanish."));
+    }
+}
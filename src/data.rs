@@ -1,6 +1,8 @@
-use std::fmt::{ self, Display, Formatter };
+use std::{ fmt::{ self, Display, Formatter }, path::PathBuf };
 use serde::{ Deserialize, Serialize };
-use derive_more::{ From, Display };
+use derive_more::From;
+
+use crate::{ agencies::GovernmentAgencies, fixed_field::FixedField, geography::Geography };
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Periodical {
@@ -101,10 +103,8 @@ impl Display for IssueNo {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             IssueNo::Number(n) => write!(f, "{}", n),
-            IssueNo::Season(s) => {
-                eprintln!("Seasonal issues are not supported yet, the issue number will be left blank. Verify issue \"{}\".", s);
-                write!(f, "")
-            }
+            // Seasonal issues are not supported yet, the issue number is left blank.
+            IssueNo::Season(_) => write!(f, ""),
         }
     }
 }
@@ -155,25 +155,87 @@ impl Display for CallNumber {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The portal's controlled vocabulary for the "Digital Format" column.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DigitalFormat {
     PDF,
+    JPEG,
+    JPEG2000,
+    TIFF,
+    PNG,
+    MP3,
+    WAV,
+    MP4,
+    /// Anything outside the vocabulary, written as the upper-cased extension.
     Other(String),
 }
 
+impl DigitalFormat {
+    /// Detects the format from the first bytes of a file, regardless of its extension.
+    ///
+    /// Returns `None` if the content doesn't match any format in the vocabulary.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        const MP4_BRANDS: [&[u8]; 12] = [
+            b"isom", b"iso2", b"iso4", b"iso5", b"iso6", b"mp41", b"mp42", b"avc1", b"dash", b"M4V ", b"M4A ", b"MSNV",
+        ];
+        const JP2_SIGNATURE: [u8; 12] = [0, 0, 0, 0x0c, b'j', b'P', b' ', b' ', 0x0d, 0x0a, 0x87, 0x0a];
+
+        // readers accept the PDF header anywhere in the first 1024 bytes
+        let pdf_window = &bytes[..bytes.len().min(1024)];
+        if pdf_window.windows(5).any(|w| w == b"%PDF-") {
+            return Some(DigitalFormat::PDF);
+        }
+        match bytes {
+            [0xff, 0xd8, 0xff, ..] => Some(DigitalFormat::JPEG),
+            _ if bytes.starts_with(&JP2_SIGNATURE) => Some(DigitalFormat::JPEG2000),
+            // a raw JPEG 2000 codestream, e.g. `.j2k`
+            [0xff, 0x4f, 0xff, 0x51, ..] => Some(DigitalFormat::JPEG2000),
+            [b'I', b'I', 42, 0, ..] | [b'M', b'M', 0, 42, ..] => Some(DigitalFormat::TIFF),
+            [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, ..] => Some(DigitalFormat::PNG),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(DigitalFormat::WAV),
+            [_, _, _, _, b'f', b't', b'y', b'p', brand @ ..] =>
+                match brand.get(..4) {
+                    Some(b"jp2 " | b"jpx " | b"jpm ") => Some(DigitalFormat::JPEG2000),
+                    Some(brand) if MP4_BRANDS.contains(&brand) => Some(DigitalFormat::MP4),
+                    // QuickTime (`qt  `), HEIC/HEIF, 3GP, and the like share the box structure but aren't MP4
+                    _ => None,
+                }
+            [b'I', b'D', b'3', ..] => Some(DigitalFormat::MP3),
+            // an MPEG audio frame without an ID3 tag
+            [0xff, second, ..] if second & 0xe0 == 0xe0 => Some(DigitalFormat::MP3),
+            _ => None,
+        }
+    }
+}
+
 impl Display for DigitalFormat {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             DigitalFormat::PDF => write!(f, "PDF"),
+            DigitalFormat::JPEG => write!(f, "JPEG"),
+            DigitalFormat::JPEG2000 => write!(f, "JPEG2000"),
+            DigitalFormat::TIFF => write!(f, "TIFF"),
+            DigitalFormat::PNG => write!(f, "PNG"),
+            DigitalFormat::MP3 => write!(f, "MP3"),
+            DigitalFormat::WAV => write!(f, "WAV"),
+            DigitalFormat::MP4 => write!(f, "MP4"),
             DigitalFormat::Other(s) => write!(f, "{}", s.to_uppercase()),
         }
     }
 }
 
+/// Maps a file extension to the vocabulary, e.g. `jpg` and `jpeg` -> [`DigitalFormat::JPEG`].
 impl From<&str> for DigitalFormat {
     fn from(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "pdf" => DigitalFormat::PDF,
+            "jpg" | "jpeg" | "jpe" | "jfif" => DigitalFormat::JPEG,
+            "jp2" | "j2k" | "jpf" | "jpx" | "jpm" => DigitalFormat::JPEG2000,
+            "tif" | "tiff" => DigitalFormat::TIFF,
+            "png" => DigitalFormat::PNG,
+            "mp3" => DigitalFormat::MP3,
+            "wav" | "wave" => DigitalFormat::WAV,
+            "mp4" | "m4v" | "m4a" => DigitalFormat::MP4,
             _ => DigitalFormat::Other(s.to_string()),
         }
     }
@@ -181,10 +243,7 @@ impl From<&str> for DigitalFormat {
 
 impl From<String> for DigitalFormat {
     fn from(s: String) -> Self {
-        match s.to_lowercase().as_str() {
-            "pdf" => DigitalFormat::PDF,
-            _ => DigitalFormat::Other(s),
-        }
+        DigitalFormat::from(s.as_str())
     }
 }
 
@@ -196,7 +255,11 @@ pub struct MarcData {
     ///
     /// MARC field: 100 or 110, sometimes 700 and 710.
     ///
-    /// Format: `First name Last name, (dates)`.
+    /// Format: `First name Last name, (dates)`, the dates only when the record has them.
+    ///
+    /// E.g. `$aSmith, John A.,$d1900-1980.` -> `John A. Smith, 1900-1980`.
+    /// Corporate names keep their subordinate units, e.g. `Arizona. Dept. of Health`.
+    /// Forenames and family names (1st indicator 0 or 3) are not inverted.
     #[serde(rename = "Creator")]
     pub creators: Vec<String>,
 
@@ -208,19 +271,29 @@ pub struct MarcData {
     ///
     /// Publishers and presses/printers are not the same. This field is for the publisher.
     ///
-    /// MARC field: 260 or 264 subfield b. (a is the place of publication, we just need b, c is the date of publication.)
+    /// MARC field: 264 subfield b with 2nd indicator 1, or 260 subfield b when there is no such 264.
+    /// (a is the place of publication, we just need b, c is the date of publication.)
+    ///
+    /// Publishers that changed over time are separated with a pipe `|`, earliest first.
+    /// See [`MarcData::publisher_for`] for the publisher of a single issue.
     #[serde(rename = "Publisher")]
     pub publisher: String,
 
+    /// Each publisher and the years it published the periodical, earliest first.
+    pub publishers: Vec<PublisherStatement>,
+
     /// Subject headings from the periodical, obtained from the catalog.
     ///
     /// Subjects are separated with a pipe `|`.
     ///
-    /// MARC field: 610, 650 (possibly any 600???).
+    /// MARC field: 600, 610, 611, 630, 650, 651, and 655, when they're Library of Congress headings
+    /// (2nd indicator 0, or 7 with `$2lcsh` or `$2lcgft`).
     ///
     /// Format examples:
     /// - `Mexico$xEconomic conditions -> Mexico--Economic conditions `.
     /// - `Water rights$zArizona. -> Water rights--Arizona`.
+    /// - `Smith, John,$d1900-1980. -> Smith, John, 1900-1980`.
+    /// - `Arizona.$bDept. of Health. -> Arizona. Dept. of Health`.
     ///
     /// If there is only one heading, it should be noted for further review by the operator, and an
     /// additional heading should be grabbed from https://authorities.loc.gov/cgi-bin/Pwebrecon.cgi?DB=local&PAGE=First.
@@ -234,12 +307,197 @@ pub struct MarcData {
     pub call_number: CallNumber,
 
     /// Obtained from the catalog. If the number is less than 9 digits, left pad with zeros.
+    /// Longer numbers are never truncated.
     ///
-    /// MARC field: 001 or 003.
+    /// MARC field: 035 subfield a with an `(OCoLC)` prefix, or 001 when 003 is `OCoLC`.
+    /// The `ocm`, `ocn`, and `on` prefixes are dropped.
     ///
     /// E.g. `004 -> 000000004`.
+    ///
+    /// `None`, and noted for review, when the record has no OCLC number.
     #[serde(rename = "OCLC Number")]
-    pub oclc_number: String,
+    pub oclc_number: Option<String>,
+
+    /// The ISSN, with a valid check digit, e.g. `0028-0836`.
+    ///
+    /// MARC field: 022 subfield a. Incorrect (`$y`) and cancelled (`$z`) ISSNs are noted for review.
+    pub issn: Option<String>,
+
+    /// The linking ISSN (ISSN-L), shared by every medium of the periodical.
+    ///
+    /// MARC field: 022 subfield l.
+    pub linking_issn: Option<String>,
+
+    /// The Library of Congress Control Number, normalized, e.g. `sn 85-1234` -> `sn85001234`.
+    ///
+    /// MARC field: 010 subfield a. Cancelled or invalid LCCNs (`$z`) are noted for review.
+    pub lccn: Option<String>,
+
+    /// The fixed-length data elements, e.g. dates, frequency, and language.
+    ///
+    /// MARC field: 008.
+    ///
+    /// E.g. `141024u19uuuuuuazumr         0    0eng d`.
+    ///
+    /// `None` when the record has no 008, or one that isn't 40 characters, e.g. collapsed by a catalog display (noted for review).
+    pub fixed_field: Option<FixedField>,
+
+    /// The current publication frequency.
+    ///
+    /// MARC field: 310 subfield a.
+    ///
+    /// E.g. `Monthly (except July and August)`.
+    pub frequency: Option<String>,
+
+    /// Former publication frequencies, oldest first.
+    ///
+    /// MARC field: 321 subfield a.
+    pub former_frequencies: Vec<String>,
+
+    /// The languages of the periodical, by their portal names, predominant language first.
+    ///
+    /// MARC field: 008/35-37 and 041 subfield a, mapped from ISO 639-2 codes.
+    ///
+    /// E.g. `eng`, `spa` -> `English`, `Spanish`.
+    pub languages: Vec<String>,
+
+    /// Summaries of the periodical, used to propose its description.
+    ///
+    /// MARC field: 520 subfields a and b.
+    pub summaries: Vec<String>,
+
+    /// General notes, e.g. `"C.R. Caryl, director."`, without the ones about how the record was cataloged.
+    ///
+    /// MARC field: 500 subfield a, less anything repeated in 588 (source of description).
+    pub general_notes: Vec<String>,
+
+    /// Statements of responsibility and credits, used to suggest contributors.
+    ///
+    /// MARC field: 245 subfield c, 508 subfield a, and 511 subfield a.
+    ///
+    /// E.g. `edited by John Smith ; photographs by Jane Doe`.
+    pub credits: Vec<String>,
+
+    /// The place of publication, e.g. `Phoenix, Ariz.`.
+    ///
+    /// MARC field: 264 subfield a with 2nd indicator 1, or 260 subfield a.
+    pub place_of_publication: Option<String>,
+
+    /// The title, without ISBD punctuation.
+    ///
+    /// MARC field: 245 subfields a, b, n, and p.
+    ///
+    /// E.g. `$aAn Arizona desert-ation :$bnewsletter.` -> `An Arizona desert-ation: newsletter`.
+    pub title: Option<String>,
+
+    /// The title without its subtitle or parts, used to check the file names against the record.
+    ///
+    /// MARC field: 245 subfield a.
+    pub title_proper: Option<String>,
+
+    /// Other forms of the title, e.g. `Arizona desertation`.
+    ///
+    /// MARC field: 246 subfields a and b.
+    pub variant_titles: Vec<String>,
+
+    /// Titles this periodical continues, absorbed, or was otherwise preceded by.
+    ///
+    /// MARC field: 780.
+    pub preceding_titles: Vec<LinkedTitle>,
+
+    /// Titles this periodical is continued by, was absorbed by, or was otherwise succeeded by.
+    ///
+    /// MARC field: 785.
+    pub succeeding_titles: Vec<LinkedTitle>,
+
+    /// People the periodical is about.
+    ///
+    /// MARC field: 600, when it's a Library of Congress heading.
+    ///
+    /// Format: `First name Last name, dates`, e.g. `$aSmith, John,$d1900-1980` -> `John Smith, 1900-1980`.
+    pub persons: Vec<String>,
+
+    /// Meetings and conferences, e.g. `Arizona Water Conference (1st : 1967 : Phoenix, Ariz.)`.
+    ///
+    /// MARC field: 611 and 111.
+    pub events: Vec<String>,
+
+    /// Corporate bodies, e.g. `Arizona. Dept. of Health`.
+    ///
+    /// MARC field: 610 and 110.
+    pub agencies: Vec<String>,
+
+    /// The issuing bodies, when the periodical is a government publication.
+    ///
+    /// A government publication is detected from 008/28, 086, and 110 or 710 jurisdiction names.
+    /// The bodies come from 110 and 710, placed by the agency table in the config file.
+    pub government_agencies: GovernmentAgencies,
+
+    /// The places the periodical is about, or was published in when it has no subject geography.
+    ///
+    /// MARC field: 651 subfield a, 6XX subfield z, 043 subfield a, and 260 or 264 subfield a.
+    ///
+    /// E.g. `Phoenix (Ariz.)` -> City or Town `Phoenix`, County `Maricopa County`, State `Arizona`.
+    pub geography: Geography,
+
+    /// Problems with the record for the operator to review, e.g. conflicting OCLC numbers.
+    ///
+    /// These are written to the "Admin Notes" of the first issue.
+    pub review_notes: Vec<String>,
+}
+
+impl MarcData {
+    /// Keys that identify the record, for matching it to file groups and other records.
+    ///
+    /// E.g. `oclc:000012345`, `issn:0028-0836`, `issn-l:0028-0836`, `lccn:sn85001234`.
+    pub fn match_keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        keys.extend(self.oclc_number.iter().map(|oclc| format!("oclc:{}", oclc)));
+        keys.extend(self.issn.iter().map(|issn| format!("issn:{}", issn)));
+        keys.extend(self.linking_issn.iter().map(|issn| format!("issn-l:{}", issn)));
+        keys.extend(self.lccn.iter().map(|lccn| format!("lccn:{}", lccn)));
+        keys
+    }
+
+    /// The publisher of an issue, by the years of each publication statement.
+    ///
+    /// Falls back to every publisher, pipe-joined, when the years don't settle it.
+    pub fn publisher_for(&self, date: &str) -> String {
+        let year = date.get(..4).and_then(|year| year.parse::<u16>().ok());
+        let publisher = year.and_then(|year| {
+            self.publishers
+                .iter()
+                .filter(|p| p.start_year.is_some_and(|start| start <= year))
+                .filter(|p| p.end_year.is_none_or(|end| year <= end))
+                .max_by_key(|p| p.start_year)
+        });
+        match publisher {
+            Some(publisher) => publisher.name.clone(),
+            None => self.publisher.clone(),
+        }
+    }
+}
+
+/// A title change, from a 780 or 785 linking entry.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinkedTitle {
+    /// How the titles are related, from the 2nd indicator, e.g. `Continues` or `Continued by`.
+    pub relationship: String,
+    /// E.g. `Arizona desert-ation`.
+    pub title: String,
+    /// The linked record's OCLC number, from `$w` with an `(OCoLC)` prefix.
+    pub oclc_number: Option<String>,
+    /// The linked record's ISSN, from `$x`.
+    pub issn: Option<String>,
+}
+
+/// A publisher and the years it published the periodical, from one 260 or 264.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PublisherStatement {
+    pub name: String,
+    pub start_year: Option<u16>,
+    /// `None` while the periodical is still published, or when the record doesn't say.
+    pub end_year: Option<u16>,
 }
 
 /// The data extracted from the file name.
@@ -253,6 +511,8 @@ pub struct IssueFileData {
     pub date_range: String,
     /// The format of the file.
     pub format: DigitalFormat,
+    /// Where the file was found.
+    pub path: PathBuf,
 }
 
 pub trait NodeTitleWithDate {
@@ -374,4 +634,28 @@ pub struct Issue {
     /// The digitizing institution.
     #[serde(rename = "Digitizing Institution")]
     pub digitizing_institution: DigitizingInstitution,
+
+    /// Notes for the operator or the portal staff, e.g. missing issues in the run.
+    ///
+    /// Multiple notes are separated with a semicolon `;`.
+    #[serde(rename = "Admin Notes")]
+    pub admin_notes: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ftyp(brand: &[u8; 4]) -> Vec<u8> {
+        [&[0, 0, 0, 0x18], &b"ftyp"[..], brand, &[0, 0, 0, 0]].concat()
+    }
+
+    #[test]
+    fn detects_mp4_brands_only() {
+        assert_eq!(DigitalFormat::detect(&ftyp(b"isom")), Some(DigitalFormat::MP4));
+        assert_eq!(DigitalFormat::detect(&ftyp(b"mp42")), Some(DigitalFormat::MP4));
+        assert_eq!(DigitalFormat::detect(&ftyp(b"jp2 ")), Some(DigitalFormat::JPEG2000));
+        assert_eq!(DigitalFormat::detect(&ftyp(b"qt  ")), None);
+        assert_eq!(DigitalFormat::detect(&ftyp(b"heic")), None);
+    }
 }
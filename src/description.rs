@@ -0,0 +1,147 @@
+use crate::{ contributors::is_credit, gaps::IssueDate, IssueFileData, MarcData };
+
+/// Placeholders a description template can use.
+const PLACEHOLDERS: [&str; 6] = ["{title}", "{publisher}", "{place}", "{date_span}", "{summary}", "{notes}"];
+
+/// Proposes a description of the periodical for the operator to edit.
+///
+/// Each sentence of the template is kept only if every placeholder in it has a value, so a record
+/// without a 520 or a place of publication still gets the sentences it can fill. `{notes}` leaves out
+/// credits such as `C.R. Caryl, director.`, which are offered as contributors instead.
+pub fn propose_description(
+    template: &[String],
+    title: &str,
+    marc: &MarcData,
+    issues: &[IssueFileData]
+) -> String {
+    let publisher = marc.publisher.replace('|', " and ");
+    let place = marc.place_of_publication.clone().unwrap_or_default();
+    let summary = marc.summaries.join(" ");
+    let notes = marc.general_notes
+        .iter()
+        .filter(|note| !is_credit(note))
+        .map(|note| end_sentence(note))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let date_span = date_span(issues);
+    let values = [title, &publisher, &place, &date_span, &summary, &notes];
+
+    template
+        .iter()
+        .filter_map(|sentence| {
+            let mut sentence = sentence.clone();
+            for (placeholder, value) in PLACEHOLDERS.iter().zip(values) {
+                if !sentence.contains(placeholder) {
+                    continue;
+                }
+                if value.trim().is_empty() {
+                    return None;
+                }
+                // a value ending a sentence itself, e.g. `Phoenix, Ariz.`, takes the place of the template's period
+                let value = value.trim();
+                if value.ends_with(['.', '!', '?']) {
+                    sentence = sentence.replace(&format!("{}.", placeholder), value);
+                }
+                sentence = sentence.replace(placeholder, value);
+            }
+            Some(end_sentence(&sentence))
+        })
+        .filter(|sentence| !sentence.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The years the issues cover, e.g. `1967` or `1967 to 1975`.
+fn date_span(issues: &[IssueFileData]) -> String {
+    let years = issues
+        .iter()
+        .filter_map(|issue| IssueDate::parse(&issue.date_original))
+        .map(|date| date.year)
+        .collect::<Vec<_>>();
+    match (years.iter().min(), years.iter().max()) {
+        (Some(first), Some(last)) if first == last => first.to_string(),
+        (Some(first), Some(last)) => format!("{} to {}", first, last),
+        _ => String::new(),
+    }
+}
+
+/// Makes sure a sentence ends with a period, since MARC notes often don't.
+fn end_sentence(sentence: &str) -> String {
+    let sentence = sentence.trim();
+    if sentence.is_empty() || sentence.ends_with(['.', '!', '?', '"']) {
+        sentence.to_string()
+    } else {
+        format!("{}.", sentence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{ config::Config, marc::parse_marc, DigitalFormat };
+
+    fn describe(lines: &[&str], dates: &[&str]) -> String {
+        let config = Config::default();
+        let marc = parse_marc(lines.join("\n").as_bytes(), &config).unwrap();
+        let issues = dates
+            .iter()
+            .map(|date| IssueFileData {
+                node_title: "An Arizona Desert-ation".to_string(),
+                date_original: date.to_string(),
+                date_range: "1960s (1960-1969)".to_string(),
+                format: DigitalFormat::PDF,
+                path: PathBuf::new(),
+            })
+            .collect::<Vec<_>>();
+        propose_description(&config.description_template, "An Arizona Desert-ation", &marc, &issues)
+    }
+
+    #[test]
+    fn fills_the_default_template() {
+        let description = describe(
+            &[
+                "264\t 1\t$aPhoenix, Ariz. :$bArizona Highway Dept.,$c1967-1975.",
+                "520\t  \t$aA newsletter for highway employees.",
+                "500\t  \t$aIncludes an annual index",
+            ],
+            &["1967-04", "1975-12"]
+        );
+        assert_eq!(
+            description,
+            "A newsletter for highway employees. An Arizona Desert-ation was published by Arizona Highway Dept in Phoenix, Ariz. These issues cover 1967 to 1975. Includes an annual index."
+        );
+    }
+
+    #[test]
+    fn drops_sentences_with_missing_values() {
+        let description = describe(&["245\t03\t$aAn Arizona desert-ation."], &["1967-04"]);
+        assert_eq!(description, "These issues cover 1967.");
+        assert_eq!(describe(&["245\t03\t$aAn Arizona desert-ation."], &[]), "");
+    }
+
+    #[test]
+    fn leaves_credits_and_cataloging_notes_out_of_the_notes() {
+        let description = describe(
+            &[
+                "500\t  \t$aC.R. Caryl, director.",
+                "500\t  \t$aEditor: John Smith.",
+                "500\t  \t$aDescription based on: Vol. 1, no. 1 (Apr. 1967).",
+                "500\t  \t$aSome issues are in Spanish.",
+            ],
+            &[]
+        );
+        assert_eq!(description, "Some issues are in Spanish.");
+    }
+
+    #[test]
+    fn collapses_doubled_punctuation() {
+        let marc = parse_marc("264\t 1\t$aWashington, D.C. :$bU.S. Govt. Print. Off.".as_bytes(), &Config::default()).unwrap();
+        let template = ["Published in {place}.".to_string(), "Printed by the {publisher}.".to_string()];
+        assert_eq!(
+            propose_description(&template, "An Arizona Desert-ation", &marc, &[]),
+            "Published in Washington, D.C. Printed by the U.S. Govt. Print. Off."
+        );
+    }
+}
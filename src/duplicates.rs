@@ -0,0 +1,182 @@
+use std::{ collections::HashMap, fmt::{ self, Display, Formatter }, fs::File, path::Path };
+
+use dialoguer::{ theme::ColorfulTheme, Select };
+use robo_archiver::ArchiveError;
+use sha2::{ Digest, Sha256 };
+
+use crate::{ cli::DuplicatePolicy, IssueFileData, NodeTitleWithDate };
+
+/// Files that would become the same issue, or that have the exact same content.
+#[derive(Debug)]
+pub struct DuplicateSet {
+    pub files: Vec<IssueFileData>,
+    /// `true` if every file in the set is byte-identical.
+    pub is_identical: bool,
+}
+
+impl Display for DuplicateSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let titles = self.files
+            .iter()
+            .map(|file| file.node_title_with_date())
+            .fold(Vec::<String>::new(), |mut titles, title| {
+                if !titles.contains(&title) {
+                    titles.push(title);
+                }
+                titles
+            });
+        let kind = if self.is_identical { "byte-identical files" } else { "files" };
+        write!(f, "{} {} for \"{}\"", self.files.len(), kind, titles.join("\", \""))
+    }
+}
+
+/// Removes duplicate issues according to `policy`.
+///
+/// Two files are duplicates if they have the same title and date (e.g. `Title_1967-04.pdf` and
+/// `sub/Title_1967-04.PDF`), or if their content hashes match regardless of name.
+pub fn resolve_duplicates(
+    files: Vec<IssueFileData>,
+    policy: DuplicatePolicy
+) -> Result<Vec<IssueFileData>, ArchiveError> {
+    let (mut kept, sets) = find_duplicates(files)?;
+    if sets.is_empty() {
+        return Ok(kept);
+    }
+
+    eprintln!("[WARN] {} set(s) of duplicate issues were found:", sets.len());
+    for set in &sets {
+        eprintln!("  - {}", set);
+        for file in &set.files {
+            eprintln!("      {:?}", file.path);
+        }
+    }
+
+    for set in sets {
+        match policy {
+            DuplicatePolicy::Fail => {
+                return Err(ArchiveError::DuplicateIssues(set.to_string()));
+            }
+            DuplicatePolicy::KeepFirst => kept.push(set.files.into_iter().next().unwrap()),
+            DuplicatePolicy::KeepLargest => {
+                let largest = set.files
+                    .into_iter()
+                    .max_by_key(file_size)
+                    .unwrap();
+                kept.push(largest);
+            }
+            DuplicatePolicy::Prompt => {
+                let mut items = set.files
+                    .iter()
+                    .map(|file| format!("{} ({} bytes)", file.path.display(), file_size(file)))
+                    .collect::<Vec<_>>();
+                items.push("Keep all of them".to_string());
+                let selection = Select::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!("Which file should be kept for the {}?", set))
+                    .items(&items)
+                    .default(0)
+                    .interact()
+                    .unwrap();
+                if selection == set.files.len() {
+                    kept.extend(set.files);
+                } else {
+                    kept.push(set.files.into_iter().nth(selection).unwrap());
+                }
+            }
+        }
+    }
+
+    Ok(kept)
+}
+
+/// Splits the files into those without duplicates and the sets of duplicates, each in path order.
+fn find_duplicates(
+    mut files: Vec<IssueFileData>
+) -> Result<(Vec<IssueFileData>, Vec<DuplicateSet>), ArchiveError> {
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    // union-find over file indices, joined by title and date, then by content
+    let mut parent = (0..files.len()).collect::<Vec<_>>();
+    fn root(parent: &mut [usize], i: usize) -> usize {
+        let mut i = i;
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let join = |parent: &mut Vec<usize>, a: usize, b: usize| {
+        let (a, b) = (root(parent, a), root(parent, b));
+        parent[b.max(a)] = a.min(b);
+    };
+
+    let mut by_key: HashMap<(String, String), usize> = HashMap::new();
+    for (i, file) in files.iter().enumerate() {
+        // the same key `process_files` groups periodicals by, so `Title` and `title` stay apart
+        let key = (file.node_title.clone(), file.date_original.clone());
+        if let Some(first) = by_key.get(&key) {
+            join(&mut parent, *first, i);
+        } else {
+            by_key.insert(key, i);
+        }
+    }
+
+    // only files that share a size can be identical, so only those are hashed
+    let mut by_size: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, file) in files.iter().enumerate() {
+        by_size.entry(file_size(file)).or_default().push(i);
+    }
+    let mut hashes = vec![None; files.len()];
+    let mut by_hash: HashMap<Vec<u8>, usize> = HashMap::new();
+    for indices in by_size.values().filter(|indices| indices.len() > 1) {
+        for &i in indices {
+            let hash = content_hash(&files[i].path)?;
+            hashes[i] = Some(hash.clone());
+            if let Some(first) = by_hash.get(&hash) {
+                join(&mut parent, *first, i);
+            } else {
+                by_hash.insert(hash, i);
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..files.len() {
+        groups.entry(root(&mut parent, i)).or_default().push(i);
+    }
+
+    let mut files = files.into_iter().map(Some).collect::<Vec<_>>();
+    let mut roots = groups.keys().copied().collect::<Vec<_>>();
+    roots.sort();
+    let mut unique = Vec::new();
+    let mut sets = Vec::new();
+    for r in roots {
+        let indices = &groups[&r];
+        let is_identical =
+            indices.len() > 1 &&
+            indices.iter().all(|i| hashes[*i].is_some() && hashes[*i] == hashes[indices[0]]);
+        let group = indices
+            .iter()
+            .map(|i| files[*i].take().unwrap())
+            .collect::<Vec<_>>();
+        if group.len() == 1 {
+            unique.extend(group);
+        } else {
+            sets.push(DuplicateSet { files: group, is_identical });
+        }
+    }
+
+    Ok((unique, sets))
+}
+
+fn content_hash(path: &Path) -> Result<Vec<u8>, ArchiveError> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().to_vec())
+}
+
+fn file_size(file: &IssueFileData) -> u64 {
+    std::fs::metadata(&file.path)
+        .map(|m| m.len())
+        .unwrap_or(0)
+}
@@ -1,8 +1,8 @@
-use std::path::PathBuf;
+use std::{ fs::File, io::Read, path::{ Path, PathBuf } };
 
 use robo_archiver::ArchiveError;
 
-use crate::{ DigitalFormat, IssueFileData };
+use crate::{ cli::DuplicatePolicy, duplicates::resolve_duplicates, DigitalFormat, IssueFileData };
 
 const SKIP_DIRS: [&str; 2] = ["target", "__MACOSX"];
 const SKIP_EXTS: [&str; 7] = ["rs", "toml", "csv", "xlsx", "xlsm", "xlsb", "xltx"];
@@ -73,6 +73,13 @@ fn data_to_date_range(date: String) -> String {
     format!("{}s ({}-{})", decade, decade, decade + 9)
 }
 
+/// Detects the format from the file's content. The extension is only a fallback, since scans get mislabeled.
+fn detect_file_format(file_path: &Path) -> Option<DigitalFormat> {
+    let mut head = Vec::new();
+    File::open(file_path).ok()?.take(1024).read_to_end(&mut head).ok()?;
+    DigitalFormat::detect(&head)
+}
+
 /// Transform a file name into an `IssueFileData` struct.
 ///
 /// E.g. `An_Arizona_Desert-ation_1967-04.pdf` -> `IssueFileData { node_title: "An Arizona Desert-ation", date_original: "1967-04", date_range: "1960s (1960-1969)" }`.
@@ -115,16 +122,25 @@ fn extract_data_from_file_name(file_path: PathBuf) -> Result<IssueFileData, Arch
         node_title,
         date_original: date_original.to_string(),
         date_range,
-        format: DigitalFormat::from(ext),
+        format: detect_file_format(&file_path).unwrap_or_else(|| DigitalFormat::from(ext)),
+        path: file_path.clone(),
     })
 }
 
-pub fn process_files(file_paths: Vec<PathBuf>) -> Result<Vec<Vec<IssueFileData>>, ArchiveError> {
+pub fn process_files(
+    file_paths: Vec<PathBuf>,
+    duplicate_policy: DuplicatePolicy
+) -> Result<Vec<Vec<IssueFileData>>, ArchiveError> {
     let mut periodicals: Vec<Vec<IssueFileData>> = Vec::new();
 
+    let files = file_paths
+        .into_iter()
+        .map(extract_data_from_file_name)
+        .collect::<Result<Vec<_>, _>>()?;
+    let files = resolve_duplicates(files, duplicate_policy)?;
+
     // group the files by their node_title.
-    for file_path in file_paths {
-        let data = extract_data_from_file_name(file_path)?;
+    for data in files {
         let node_title = &data.node_title;
         if
             let Some(item) = periodicals
@@ -0,0 +1,205 @@
+use serde::{ Deserialize, Serialize };
+use robo_archiver::ArchiveError;
+
+use crate::{ agencies::GovernmentLevel, gaps::Frequency };
+
+/// The length of an 008 as MARC 21 defines it.
+const LENGTH: usize = 40;
+
+/// The fixed-length data elements (008) of a continuing resource.
+///
+/// E.g. `141024u19uuuuuuazumr         0    0eng d`. The catalog's display collapses the runs of blanks, and such
+/// an 008 is rejected rather than guessed at.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FixedField {
+    /// The 008 as it appeared in the record.
+    pub raw: String,
+    /// 00-05, `yymmdd`.
+    pub date_entered: String,
+    /// 06
+    pub date_type: DateType,
+    /// 07-10, the beginning date of publication.
+    pub date1: FixedDate,
+    /// 11-14, the ending date of publication, `9999` while still published.
+    pub date2: FixedDate,
+    /// 15-17, a MARC country code, e.g. `azu` for Arizona.
+    pub place: String,
+    /// 18, see [`FixedField::frequency`].
+    pub frequency_code: char,
+    /// 19
+    pub regularity: Regularity,
+    /// 28, see [`FixedField::government_level`].
+    pub government_code: char,
+    /// 35-37, an ISO 639-2 code, e.g. `eng`.
+    pub language: String,
+    /// 39, e.g. `d` for other than the national library, `c` for cooperative cataloging.
+    pub cataloging_source: char,
+}
+
+/// The publication status and kind of dates in 008/07-14.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DateType {
+    /// `c`, date2 is `9999`.
+    CurrentlyPublished,
+    /// `d`
+    Ceased,
+    /// `u`, date2 is `uuuu`.
+    StatusUnknown,
+    Other(char),
+}
+
+impl From<char> for DateType {
+    fn from(code: char) -> Self {
+        match code {
+            'c' => DateType::CurrentlyPublished,
+            'd' => DateType::Ceased,
+            'u' => DateType::StatusUnknown,
+            code => DateType::Other(code),
+        }
+    }
+}
+
+/// Whether issues come out on schedule, 008/19.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Regularity {
+    /// `r`
+    Regular,
+    /// `n`, irregular but on a known pattern, e.g. monthly except July and August.
+    NormalizedIrregular,
+    /// `x`
+    CompletelyIrregular,
+    /// `u`, blank, or anything else.
+    Unknown,
+}
+
+impl From<char> for Regularity {
+    fn from(code: char) -> Self {
+        match code {
+            'r' => Regularity::Regular,
+            'n' => Regularity::NormalizedIrregular,
+            'x' => Regularity::CompletelyIrregular,
+            _ => Regularity::Unknown,
+        }
+    }
+}
+
+/// A year in 008/07-14, where unknown digits are `u`, e.g. `19uu` for sometime in the 1900s.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixedDate(pub String);
+
+impl FixedDate {
+    /// The earliest year it could be, e.g. `19uu -> 1900`. `None` when blank or entirely unknown.
+    pub fn earliest(&self) -> Option<u16> {
+        self.year('0')
+    }
+
+    /// The latest year it could be, e.g. `19uu -> 1999`. `None` when blank or entirely unknown.
+    pub fn latest(&self) -> Option<u16> {
+        self.year('9')
+    }
+
+    /// `9999`, the periodical is still published.
+    pub fn is_open(&self) -> bool {
+        self.0 == "9999"
+    }
+
+    fn year(&self, unknown: char) -> Option<u16> {
+        if self.0.len() != 4 || self.0 == "uuuu" {
+            return None;
+        }
+        self.0
+            .replace('u', &unknown.to_string())
+            .parse()
+            .ok()
+    }
+}
+
+impl FixedField {
+    /// Parses an 008 of exactly 40 characters.
+    pub fn parse(value: &str) -> Result<Self, ArchiveError> {
+        let chars = value.chars().collect::<Vec<_>>();
+        if chars.len() != LENGTH {
+            return Err(
+                ArchiveError::InvalidFixedField(
+                    format!("expected {} characters, found {} in \"{}\"", LENGTH, chars.len(), value)
+                )
+            );
+        }
+        Ok(Self::from_parts(value, &chars[..20], chars[28], &chars[35..]))
+    }
+
+    /// Builds the struct from 00-19, 28, and 35-39.
+    fn from_parts(raw: &str, start: &[char], government_code: char, end: &[char]) -> Self {
+        let text = |chars: &[char]| chars.iter().collect::<String>();
+        FixedField {
+            raw: raw.to_string(),
+            date_entered: text(&start[0..6]),
+            date_type: DateType::from(start[6]),
+            date1: FixedDate(text(&start[7..11])),
+            date2: FixedDate(text(&start[11..15])),
+            place: text(&start[15..18]).trim().to_string(),
+            frequency_code: start[18],
+            regularity: Regularity::from(start[19]),
+            government_code,
+            language: text(&end[0..3]),
+            cataloging_source: end[4],
+        }
+    }
+
+    /// The expected frequency, `None` for irregular, unknown, or no determinable frequency.
+    pub fn frequency(&self) -> Option<Frequency> {
+        Frequency::from_code(self.frequency_code)
+    }
+
+    /// The level of government that issued it, `None` if it isn't a government publication.
+    pub fn government_level(&self) -> Option<GovernmentLevel> {
+        GovernmentLevel::from_code(self.government_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_continuing_resource_008() {
+        let fixed_field = FixedField::parse("750101c19679999azumr p      s0   a0spa d").unwrap();
+        assert_eq!(fixed_field.date_entered, "750101");
+        assert_eq!(fixed_field.date_type, DateType::CurrentlyPublished);
+        assert_eq!(fixed_field.date1, FixedDate("1967".to_string()));
+        assert!(fixed_field.date2.is_open());
+        assert_eq!(fixed_field.place, "azu");
+        assert_eq!(fixed_field.frequency(), Some(Frequency::Monthly));
+        assert_eq!(fixed_field.regularity, Regularity::Regular);
+        assert_eq!(fixed_field.government_level(), Some(GovernmentLevel::State));
+        assert_eq!(fixed_field.language, "spa");
+        assert_eq!(fixed_field.cataloging_source, 'd');
+    }
+
+    #[test]
+    fn rejects_an_008_that_is_not_40_characters() {
+        // as the catalog displays it, with the runs of blanks collapsed
+        assert!(matches!(
+            FixedField::parse("141024u19uuuuuuazumr 0 0eng d"),
+            Err(ArchiveError::InvalidFixedField(_))
+        ));
+        assert!(matches!(
+            FixedField::parse("750101c19679999azumr p      s0   a0spa d "),
+            Err(ArchiveError::InvalidFixedField(_))
+        ));
+        assert!(matches!(FixedField::parse(""), Err(ArchiveError::InvalidFixedField(_))));
+    }
+
+    #[test]
+    fn reads_unknown_digits_as_a_range_of_years() {
+        let date = FixedDate("19uu".to_string());
+        assert_eq!(date.earliest(), Some(1900));
+        assert_eq!(date.latest(), Some(1999));
+        let date = FixedDate("196u".to_string());
+        assert_eq!(date.earliest(), Some(1960));
+        assert_eq!(date.latest(), Some(1969));
+        assert_eq!(FixedDate("uuuu".to_string()).earliest(), None);
+        assert_eq!(FixedDate("    ".to_string()).latest(), None);
+        assert!(!FixedDate("1967".to_string()).is_open());
+    }
+}
@@ -0,0 +1,547 @@
+use std::fmt::{ self, Display, Formatter };
+
+use crate::{ IssueFileData, MarcData };
+
+/// How often a periodical is published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Biweekly,
+    Semimonthly,
+    Monthly,
+    Bimonthly,
+    Quarterly,
+    ThreeTimesAYear,
+    Semiannual,
+    Annual,
+    Biennial,
+    Triennial,
+}
+
+/// Where the expected frequency came from, for the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrequencySource {
+    /// MARC 310 or 321.
+    Statement,
+    /// MARC 008/18.
+    FixedField,
+    /// Guessed from the spacing of the issue dates.
+    Inferred,
+}
+
+/// The expected frequency of a run, and the months it skips, e.g. `Monthly (except July and August)`.
+#[derive(Debug, Clone)]
+pub struct ExpectedFrequency {
+    pub frequency: Frequency,
+    pub source: FrequencySource,
+    /// 1-based months with no issue.
+    pub excluded_months: Vec<u32>,
+}
+
+impl Frequency {
+    /// Parses a frequency statement such as `Monthly` or `Three times a year`.
+    fn from_statement(statement: &str) -> Option<Self> {
+        let statement = statement.to_lowercase();
+        let frequency = statement.split(['(', ',', ';']).next().unwrap_or_default().trim();
+        Some(match frequency {
+            f if f.contains("semimonthly") || f.contains("semi-monthly") || f.contains("twice a month") =>
+                Frequency::Semimonthly,
+            f if f.contains("bimonthly") || f.contains("bi-monthly") || f.contains("every two months") =>
+                Frequency::Bimonthly,
+            f if f.contains("biweekly") || f.contains("bi-weekly") || f.contains("every two weeks") =>
+                Frequency::Biweekly,
+            f if f.contains("semiannual") || f.contains("semi-annual") || f.contains("twice a year") =>
+                Frequency::Semiannual,
+            f if f.contains("biennial") => Frequency::Biennial,
+            f if f.contains("triennial") => Frequency::Triennial,
+            f if f.contains("three times a year") => Frequency::ThreeTimesAYear,
+            f if f.contains("quarterly") => Frequency::Quarterly,
+            f if f.contains("monthly") => Frequency::Monthly,
+            f if f.contains("weekly") => Frequency::Weekly,
+            f if f.contains("daily") => Frequency::Daily,
+            f if f.contains("annual") || f.contains("yearly") => Frequency::Annual,
+            _ => {
+                return None;
+            }
+        })
+    }
+
+    /// Parses the frequency code at 008/18 for continuing resources.
+    pub(crate) fn from_code(code: char) -> Option<Self> {
+        Some(match code {
+            'd' => Frequency::Daily,
+            'w' => Frequency::Weekly,
+            'e' => Frequency::Biweekly,
+            's' => Frequency::Semimonthly,
+            'm' => Frequency::Monthly,
+            'b' => Frequency::Bimonthly,
+            'q' => Frequency::Quarterly,
+            't' => Frequency::ThreeTimesAYear,
+            'f' => Frequency::Semiannual,
+            'a' => Frequency::Annual,
+            'g' => Frequency::Biennial,
+            'h' => Frequency::Triennial,
+            // irregular, unknown, or no determinable frequency
+            _ => {
+                return None;
+            }
+        })
+    }
+
+    /// The distance between issues.
+    fn step(&self) -> Step {
+        match self {
+            Frequency::Daily => Step::Days(1),
+            Frequency::Weekly => Step::Days(7),
+            Frequency::Biweekly => Step::Days(14),
+            Frequency::Semimonthly => Step::HalfMonths,
+            Frequency::Monthly => Step::Months(1),
+            Frequency::Bimonthly => Step::Months(2),
+            Frequency::Quarterly => Step::Months(3),
+            Frequency::ThreeTimesAYear => Step::Months(4),
+            Frequency::Semiannual => Step::Months(6),
+            Frequency::Annual => Step::Months(12),
+            Frequency::Biennial => Step::Months(24),
+            Frequency::Triennial => Step::Months(36),
+        }
+    }
+}
+
+impl Display for Frequency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Frequency::Daily => "daily",
+            Frequency::Weekly => "weekly",
+            Frequency::Biweekly => "biweekly",
+            Frequency::Semimonthly => "semimonthly",
+            Frequency::Monthly => "monthly",
+            Frequency::Bimonthly => "bimonthly",
+            Frequency::Quarterly => "quarterly",
+            Frequency::ThreeTimesAYear => "three times a year",
+            Frequency::Semiannual => "semiannual",
+            Frequency::Annual => "annual",
+            Frequency::Biennial => "biennial",
+            Frequency::Triennial => "triennial",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl ExpectedFrequency {
+    /// Reads the frequency from the MARC record: 310 first, then 008/18, then the latest 321.
+    pub fn from_marc(marc: &MarcData) -> Option<Self> {
+        if let Some(statement) = &marc.frequency {
+            if let Some(frequency) = Frequency::from_statement(statement) {
+                return Some(ExpectedFrequency {
+                    frequency,
+                    source: FrequencySource::Statement,
+                    excluded_months: excluded_months(statement),
+                });
+            }
+        }
+        if let Some(frequency) = marc.fixed_field.as_ref().and_then(|f| f.frequency()) {
+            return Some(ExpectedFrequency {
+                frequency,
+                source: FrequencySource::FixedField,
+                excluded_months: Vec::new(),
+            });
+        }
+        let statement = marc.former_frequencies.last()?;
+        Frequency::from_statement(statement).map(|frequency| ExpectedFrequency {
+            frequency,
+            source: FrequencySource::Statement,
+            excluded_months: excluded_months(statement),
+        })
+    }
+
+    /// Guesses the frequency from the median distance between issues. Needs at least 3 issues.
+    pub fn infer(dates: &[IssueDate]) -> Option<Self> {
+        if dates.len() < 3 {
+            return None;
+        }
+        let frequency = if dates.iter().all(|d| d.day.is_some()) {
+            let mut distances = dates
+                .windows(2)
+                .map(|pair| pair[1].days() - pair[0].days())
+                .collect::<Vec<_>>();
+            distances.sort();
+            match distances[distances.len() / 2] {
+                1..=2 => Frequency::Daily,
+                6..=8 => Frequency::Weekly,
+                13..=14 => Frequency::Biweekly,
+                15..=16 => Frequency::Semimonthly,
+                days => Self::from_months(((days as f64) / 30.4).round() as i64)?,
+            }
+        } else if dates.iter().all(|d| d.month.is_some()) {
+            let mut distances = dates
+                .windows(2)
+                .map(|pair| pair[1].months() - pair[0].months())
+                .collect::<Vec<_>>();
+            distances.sort();
+            Self::from_months(distances[distances.len() / 2])?
+        } else {
+            let mut distances = dates
+                .windows(2)
+                .map(|pair| pair[1].year - pair[0].year)
+                .collect::<Vec<_>>();
+            distances.sort();
+            Self::from_months(distances[distances.len() / 2] * 12)?
+        };
+        Some(ExpectedFrequency { frequency, source: FrequencySource::Inferred, excluded_months: Vec::new() })
+    }
+
+    fn from_months(months: i64) -> Option<Frequency> {
+        Some(match months {
+            1 => Frequency::Monthly,
+            2 => Frequency::Bimonthly,
+            3 => Frequency::Quarterly,
+            4 => Frequency::ThreeTimesAYear,
+            6 => Frequency::Semiannual,
+            12 => Frequency::Annual,
+            24 => Frequency::Biennial,
+            36 => Frequency::Triennial,
+            _ => {
+                return None;
+            }
+        })
+    }
+}
+
+impl Display for ExpectedFrequency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let source = match self.source {
+            FrequencySource::Statement => "from the frequency statement",
+            FrequencySource::FixedField => "from the 008",
+            FrequencySource::Inferred => "inferred from the issue dates",
+        };
+        write!(f, "{} ({})", self.frequency, source)
+    }
+}
+
+/// Finds the months named after "except" in a statement like `Monthly (except July and August)`.
+fn excluded_months(statement: &str) -> Vec<u32> {
+    const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    let statement = statement.to_lowercase();
+    let Some((_, except)) = statement.split_once("except") else {
+        return Vec::new();
+    };
+    except
+        .split(|c: char| !c.is_alphabetic())
+        .filter_map(|word| {
+            MONTHS.iter()
+                .position(|month| word.len() >= 3 && word.starts_with(month))
+                .map(|i| (i + 1) as u32)
+        })
+        .collect()
+}
+
+enum Step {
+    Days(i64),
+    /// Issues on the 1st and 15th.
+    HalfMonths,
+    Months(i64),
+}
+
+/// A partial `yyyy-mm-dd` date from a file name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IssueDate {
+    pub year: i64,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+}
+
+impl IssueDate {
+    pub fn parse(date: &str) -> Option<Self> {
+        let mut parts = date.split('-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next().and_then(|m| m.parse().ok()).filter(|m| (1..=12).contains(m));
+        let day = parts.next().and_then(|d| d.parse().ok()).filter(|d| (1..=31).contains(d));
+        Some(IssueDate { year, month, day: month.and(day) })
+    }
+
+    fn months(&self) -> i64 {
+        self.year * 12 + (self.month.unwrap_or(1) as i64) - 1
+    }
+
+    /// The date `months` after year 0, with `day` moved back to the end of shorter months, e.g. the 31st
+    /// in February is the 28th.
+    fn from_months(months: i64, day: Option<u32>) -> Self {
+        let year = months.div_euclid(12);
+        let month = (months.rem_euclid(12) + 1) as u32;
+        IssueDate {
+            year,
+            month: Some(month),
+            day: day.map(|day| day.min(days_in_month(year, month))),
+        }
+    }
+
+    /// Half months since year 0, the first half before the 15th.
+    fn half_months(&self) -> i64 {
+        self.months() * 2 + (if self.day.unwrap_or(1) >= 15 { 1 } else { 0 })
+    }
+
+    /// The inverse of [`IssueDate::half_months`], on the 1st or the 15th.
+    fn from_half_months(half_months: i64) -> Self {
+        let day = if half_months.rem_euclid(2) == 0 { 1 } else { 15 };
+        IssueDate::from_months(half_months.div_euclid(2), Some(day))
+    }
+
+    /// Days since 1970-01-01, from Howard Hinnant's `days_from_civil`.
+    fn days(&self) -> i64 {
+        let (m, d) = (self.month.unwrap_or(1) as i64, self.day.unwrap_or(1) as i64);
+        let y = if m <= 2 { self.year - 1 } else { self.year };
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    /// The inverse of [`IssueDate::days`].
+    fn from_days(days: i64) -> Self {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+        let year = yoe + era * 400 + (if month <= 2 { 1 } else { 0 });
+        IssueDate { year, month: Some(month), day: Some(day) }
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+impl Display for IssueDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.month, self.day) {
+            (Some(month), Some(day)) => write!(f, "{:04}-{:02}-{:02}", self.year, month, day),
+            (Some(month), None) => write!(f, "{:04}-{:02}", self.year, month),
+            _ => write!(f, "{:04}", self.year),
+        }
+    }
+}
+
+/// One or more consecutive issues missing from a run.
+#[derive(Debug, Clone)]
+pub struct Gap {
+    pub missing: Vec<IssueDate>,
+    /// The `date_original` of the issue before the gap.
+    pub after: String,
+    /// The `date_original` of the issue after the gap.
+    pub before: String,
+}
+
+/// Gaps with more missing issues than this are written as a range, so a daily with a hole of
+/// several years doesn't list thousands of dates in one cell.
+const MAX_LISTED_MISSING: usize = 3;
+
+impl Display for Gap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let missing = match self.missing.as_slice() {
+            [first, .., last] if self.missing.len() > MAX_LISTED_MISSING =>
+                format!("{} to {} ({} issues)", first, last, self.missing.len()),
+            missing => missing
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        write!(f, "{} missing between {} and {}", missing, self.after, self.before)
+    }
+}
+
+/// Finds missing issues in a sorted run.
+///
+/// Dates coarser than the frequency (e.g. `yyyy` for a monthly) can't show gaps, so they're skipped.
+pub fn find_gaps(issues: &[IssueFileData], expected: &ExpectedFrequency) -> Vec<Gap> {
+    let mut gaps = Vec::new();
+    for pair in issues.windows(2) {
+        let (Some(a), Some(b)) = (
+            IssueDate::parse(&pair[0].date_original),
+            IssueDate::parse(&pair[1].date_original),
+        ) else {
+            continue;
+        };
+
+        let missing = match expected.frequency.step() {
+            Step::Days(step) => {
+                if a.day.is_none() || b.day.is_none() {
+                    continue;
+                }
+                let distance = b.days() - a.days();
+                let count = ((distance as f64) / (step as f64)).round() as i64;
+                (1..count).map(|k| IssueDate::from_days(a.days() + k * step)).collect::<Vec<_>>()
+            }
+            Step::HalfMonths => {
+                if a.day.is_none() || b.day.is_none() {
+                    continue;
+                }
+                (a.half_months() + 1..b.half_months()).map(IssueDate::from_half_months).collect::<Vec<_>>()
+            }
+            Step::Months(step) => {
+                if step < 12 && (a.month.is_none() || b.month.is_none()) {
+                    continue;
+                }
+                let distance = b.months() - a.months();
+                let count = ((distance as f64) / (step as f64)).round() as i64;
+                (1..count)
+                    .map(|k| {
+                        let date = IssueDate::from_months(a.months() + k * step, a.day);
+                        // keep the precision of the file names, e.g. `1967` for annuals
+                        if a.month.is_none() {
+                            IssueDate { month: None, day: None, ..date }
+                        } else {
+                            date
+                        }
+                    })
+                    .filter(|date| date.month.is_none_or(|m| !expected.excluded_months.contains(&m)))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        if !missing.is_empty() {
+            gaps.push(Gap {
+                missing,
+                after: pair[0].date_original.clone(),
+                before: pair[1].date_original.clone(),
+            });
+        }
+    }
+    gaps
+}
+
+/// Works out the expected frequency for a run, from the MARC record or the dates themselves.
+pub fn expected_frequency(issues: &[IssueFileData], marc: &MarcData) -> Option<ExpectedFrequency> {
+    ExpectedFrequency::from_marc(marc).or_else(|| {
+        let dates = issues
+            .iter()
+            .filter_map(|i| IssueDate::parse(&i.date_original))
+            .collect::<Vec<_>>();
+        ExpectedFrequency::infer(&dates)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::DigitalFormat;
+
+    fn issues(dates: &[&str]) -> Vec<IssueFileData> {
+        dates
+            .iter()
+            .map(|date| IssueFileData {
+                node_title: "An Arizona Desert-ation".to_string(),
+                date_original: date.to_string(),
+                date_range: "1960s (1960-1969)".to_string(),
+                format: DigitalFormat::PDF,
+                path: PathBuf::new(),
+            })
+            .collect()
+    }
+
+    fn dates(dates: &[&str]) -> Vec<IssueDate> {
+        dates.iter().filter_map(|date| IssueDate::parse(date)).collect()
+    }
+
+    fn expected(frequency: Frequency, excluded_months: &[u32]) -> ExpectedFrequency {
+        ExpectedFrequency { frequency, source: FrequencySource::Statement, excluded_months: excluded_months.to_vec() }
+    }
+
+    /// The missing dates of each gap.
+    fn missing(dates: &[&str], expected: &ExpectedFrequency) -> Vec<Vec<String>> {
+        find_gaps(&issues(dates), expected)
+            .iter()
+            .map(|gap| gap.missing.iter().map(|date| date.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn finds_holes_in_a_monthly_run() {
+        let monthly = expected(Frequency::Monthly, &[]);
+        assert_eq!(
+            missing(&["1967-01", "1967-02", "1967-04", "1967-05", "1967-08"], &monthly),
+            [vec!["1967-03"], vec!["1967-06", "1967-07"]]
+        );
+        assert!(missing(&["1967-11", "1967-12", "1968-01"], &monthly).is_empty());
+    }
+
+    #[test]
+    fn skips_excluded_months() {
+        let statement = "Monthly (except July and Aug.)";
+        assert_eq!(Frequency::from_statement(statement), Some(Frequency::Monthly));
+        assert_eq!(excluded_months(statement), [7, 8]);
+        let summers_off = expected(Frequency::Monthly, &excluded_months(statement));
+        assert!(missing(&["1967-05", "1967-06", "1967-09"], &summers_off).is_empty());
+        assert_eq!(missing(&["1967-05", "1967-09"], &summers_off), [vec!["1967-06"]]);
+    }
+
+    #[test]
+    fn semimonthly_issues_are_on_the_1st_and_15th() {
+        let semimonthly = expected(Frequency::Semimonthly, &[]);
+        assert_eq!(
+            missing(&["1967-04-01", "1967-05-15"], &semimonthly),
+            [vec!["1967-04-15", "1967-05-01"]]
+        );
+        assert!(missing(&["1967-04-01", "1967-04-16", "1967-05-01"], &semimonthly).is_empty());
+    }
+
+    #[test]
+    fn days_past_the_end_of_a_month_are_moved_back() {
+        let monthly = expected(Frequency::Monthly, &[]);
+        assert_eq!(
+            missing(&["1967-01-31", "1967-04-30"], &monthly),
+            [vec!["1967-02-28", "1967-03-31"]]
+        );
+        assert_eq!(missing(&["1968-01-31", "1968-03-31"], &monthly), [vec!["1968-02-29"]]);
+    }
+
+    #[test]
+    fn infers_the_frequency_from_the_dates() {
+        let infer = |d: &[&str]| ExpectedFrequency::infer(&dates(d)).map(|expected| expected.frequency);
+        assert_eq!(infer(&["1967-04-01", "1967-04-08", "1967-04-22", "1967-04-29"]), Some(Frequency::Weekly));
+        assert_eq!(infer(&["1967-04-01", "1967-04-16", "1967-05-01"]), Some(Frequency::Semimonthly));
+        assert_eq!(infer(&["1967-01", "1967-02", "1967-04", "1967-05"]), Some(Frequency::Monthly));
+        assert_eq!(infer(&["1967-01", "1967-04", "1967-07"]), Some(Frequency::Quarterly));
+        assert_eq!(infer(&["1967", "1968", "1969", "1971"]), Some(Frequency::Annual));
+        // too few issues to tell
+        assert_eq!(infer(&["1967-01", "1967-02"]), None);
+        let inferred = ExpectedFrequency::infer(&dates(&["1967-01", "1967-02", "1967-03"])).unwrap();
+        assert_eq!(inferred.source, FrequencySource::Inferred);
+    }
+
+    fn gap(missing: &[&str]) -> Gap {
+        Gap {
+            missing: missing.iter().filter_map(|date| IssueDate::parse(date)).collect(),
+            after: "1967-04-01".to_string(),
+            before: "1967-07-01".to_string(),
+        }
+    }
+
+    #[test]
+    fn short_gaps_list_every_date() {
+        assert_eq!(
+            gap(&["1967-04-02", "1967-04-03"]).to_string(),
+            "1967-04-02, 1967-04-03 missing between 1967-04-01 and 1967-07-01"
+        );
+    }
+
+    #[test]
+    fn long_gaps_are_a_range() {
+        let missing = (1..90).map(|day| IssueDate::from_days(IssueDate::parse("1967-04-01").unwrap().days() + day));
+        let gap = Gap { missing: missing.collect(), ..gap(&[]) };
+        assert_eq!(gap.to_string(), "1967-04-02 to 1967-06-29 (89 issues) missing between 1967-04-01 and 1967-07-01");
+    }
+}
@@ -0,0 +1,452 @@
+use serde::{ Deserialize, Serialize };
+
+/// Arizona's counties, for telling `Pima County (Ariz.)` apart from the town of `Pima (Ariz.)`.
+const AZ_COUNTIES: [&str; 15] = [
+    "Apache",
+    "Cochise",
+    "Coconino",
+    "Gila",
+    "Graham",
+    "Greenlee",
+    "La Paz",
+    "Maricopa",
+    "Mohave",
+    "Navajo",
+    "Pima",
+    "Pinal",
+    "Santa Cruz",
+    "Yavapai",
+    "Yuma",
+];
+
+/// State names, postal codes, MARC 043 area codes, and the abbreviations used in catalog records.
+///
+/// The area codes are the postal codes in lowercase, except Nebraska's `nb`.
+const STATES: [(&str, &str, &str, &[&str]); 51] = [
+    ("Alabama", "AL", "al", &["Ala."]),
+    ("Alaska", "AK", "ak", &[]),
+    ("Arizona", "AZ", "az", &["Ariz."]),
+    ("Arkansas", "AR", "ar", &["Ark."]),
+    ("California", "CA", "ca", &["Calif.", "Cal."]),
+    ("Colorado", "CO", "co", &["Colo."]),
+    ("Connecticut", "CT", "ct", &["Conn."]),
+    ("Delaware", "DE", "de", &["Del."]),
+    ("District of Columbia", "DC", "dc", &["D.C."]),
+    ("Florida", "FL", "fl", &["Fla."]),
+    ("Georgia", "GA", "ga", &["Ga."]),
+    ("Hawaii", "HI", "hi", &[]),
+    ("Idaho", "ID", "id", &[]),
+    ("Illinois", "IL", "il", &["Ill."]),
+    ("Indiana", "IN", "in", &["Ind."]),
+    ("Iowa", "IA", "ia", &[]),
+    ("Kansas", "KS", "ks", &["Kan.", "Kans."]),
+    ("Kentucky", "KY", "ky", &["Ky."]),
+    ("Louisiana", "LA", "la", &["La."]),
+    ("Maine", "ME", "me", &["Me."]),
+    ("Maryland", "MD", "md", &["Md."]),
+    ("Massachusetts", "MA", "ma", &["Mass."]),
+    ("Michigan", "MI", "mi", &["Mich."]),
+    ("Minnesota", "MN", "mn", &["Minn."]),
+    ("Mississippi", "MS", "ms", &["Miss."]),
+    ("Missouri", "MO", "mo", &["Mo."]),
+    ("Montana", "MT", "mt", &["Mont."]),
+    ("Nebraska", "NE", "nb", &["Neb.", "Nebr."]),
+    ("Nevada", "NV", "nv", &["Nev."]),
+    ("New Hampshire", "NH", "nh", &["N.H."]),
+    ("New Jersey", "NJ", "nj", &["N.J."]),
+    ("New Mexico", "NM", "nm", &["N.M.", "N. Mex."]),
+    ("New York", "NY", "ny", &["N.Y."]),
+    ("North Carolina", "NC", "nc", &["N.C."]),
+    ("North Dakota", "ND", "nd", &["N.D.", "N. Dak."]),
+    ("Ohio", "OH", "oh", &[]),
+    ("Oklahoma", "OK", "ok", &["Okla."]),
+    ("Oregon", "OR", "or", &["Or.", "Ore."]),
+    ("Pennsylvania", "PA", "pa", &["Pa."]),
+    ("Rhode Island", "RI", "ri", &["R.I."]),
+    ("South Carolina", "SC", "sc", &["S.C."]),
+    ("South Dakota", "SD", "sd", &["S.D.", "S. Dak."]),
+    ("Tennessee", "TN", "tn", &["Tenn."]),
+    ("Texas", "TX", "tx", &["Tex."]),
+    ("Utah", "UT", "ut", &[]),
+    ("Vermont", "VT", "vt", &["Vt."]),
+    ("Virginia", "VA", "va", &["Va."]),
+    ("Washington", "WA", "wa", &["Wash."]),
+    ("West Virginia", "WV", "wv", &["W. Va."]),
+    ("Wisconsin", "WI", "wi", &["Wis.", "Wisc."]),
+    ("Wyoming", "WY", "wy", &["Wyo."]),
+];
+
+/// Words that make a place name a natural or built feature rather than a town.
+const FEATURE_WORDS: [&str; 18] = [
+    "Basin",
+    "Canyon",
+    "Creek",
+    "Dam",
+    "Desert",
+    "Forest",
+    "Lake",
+    "Mesa",
+    "Monument",
+    "Mountain",
+    "Mountains",
+    "National Park",
+    "Peak",
+    "Plateau",
+    "Range",
+    "River",
+    "Valley",
+    "Wash",
+];
+
+const ROAD_WORDS: [&str; 5] = ["Highway", "Route", "Road", "Trail", "Interstate"];
+
+/// The geographic columns of the spreadsheet, each pipe-joined when written.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Geography {
+    /// Places in Library of Congress form, e.g. `Phoenix (Ariz.)`.
+    #[serde(rename = "Place")]
+    pub places: Vec<String>,
+    #[serde(rename = "City or Town")]
+    pub cities: Vec<String>,
+    /// E.g. `Salt River Valley`.
+    #[serde(rename = "Geographic Feature")]
+    pub features: Vec<String>,
+    /// E.g. `Fort Apache Indian Reservation`.
+    #[serde(rename = "Tribal Homeland")]
+    pub tribal_homelands: Vec<String>,
+    #[serde(rename = "Road")]
+    pub roads: Vec<String>,
+    /// E.g. `Maricopa County`.
+    #[serde(rename = "County")]
+    pub counties: Vec<String>,
+    #[serde(rename = "State")]
+    pub states: Vec<String>,
+    #[serde(rename = "Country")]
+    pub countries: Vec<String>,
+}
+
+/// Where a place name was found in the record.
+#[derive(Debug)]
+pub enum PlaceSource {
+    /// A 651 `$a`, or a 6XX `$z` in Library of Congress form, e.g. `Phoenix (Ariz.)`.
+    Heading(String),
+    /// Consecutive `$z` subdivisions, larger to smaller, e.g. `Arizona`, `Phoenix`.
+    Subdivisions(Vec<String>),
+    /// A MARC 043 geographic area code, e.g. `n-us-az`.
+    AreaCode(String),
+    /// A 260 or 264 place of publication, e.g. `Phoenix, Ariz.`.
+    Publication(String),
+}
+
+impl Geography {
+    /// Fills the columns from the places found in the record.
+    ///
+    /// Places of publication are only used when the record has no subject geography.
+    /// Anything that can't be placed confidently is left blank and returned as a note for review.
+    pub fn from_sources(sources: &[PlaceSource]) -> (Self, Vec<String>) {
+        let gazetteer = load_gazetteer();
+        let mut geography = Geography::default();
+        let mut notes = Vec::new();
+
+        let has_subjects = sources.iter().any(|s| !matches!(s, PlaceSource::Publication(_)));
+        for source in sources {
+            match source {
+                PlaceSource::Heading(heading) => {
+                    let heading = heading.trim().trim_end_matches(['.', ',', ' ']);
+                    push_unique(&mut geography.places, heading);
+                    let (name, qualifier) = split_qualifier(heading);
+                    geography.add_place(&gazetteer, name, qualifier, &mut notes);
+                }
+                PlaceSource::Subdivisions(names) => {
+                    let mut state: Option<&str> = None;
+                    for name in names {
+                        let name = name.trim().trim_end_matches(['.', ',', ' ']);
+                        let (name, qualifier) = split_qualifier(name);
+                        if qualifier.is_some() {
+                            geography.add_place(&gazetteer, name, qualifier, &mut notes);
+                        } else if let Some(full) = state_name(name) {
+                            push_unique(&mut geography.states, full);
+                            push_unique(&mut geography.countries, "United States");
+                            state = Some(full);
+                        } else if let Some(country) = country_name(name) {
+                            push_unique(&mut geography.countries, country);
+                        } else if state == Some("Arizona") && AZ_COUNTIES.contains(&name) {
+                            // `$zArizona$zPima` could be the town or the county
+                            notes.push(
+                                format!("\"{}\" (Ariz.) could be a town or a county, it was left for review.", name)
+                            );
+                        } else {
+                            // an indirect subdivision, e.g. `$zArizona$zPhoenix`
+                            geography.add_place(&gazetteer, name, state, &mut notes);
+                        }
+                    }
+                }
+                PlaceSource::AreaCode(code) => {
+                    let code = code.trim().trim_end_matches('-').to_lowercase();
+                    let mut parts = code.split('-');
+                    match (parts.next(), parts.next(), parts.next()) {
+                        (Some("n"), Some("us"), Some(area)) => {
+                            push_unique(&mut geography.countries, "United States");
+                            if let Some((name, ..)) = STATES.iter().find(|(_, _, area_code, _)| *area_code == area) {
+                                push_unique(&mut geography.states, name);
+                            }
+                        }
+                        (Some("n"), Some("us"), None) => push_unique(&mut geography.countries, "United States"),
+                        (Some("n"), Some("mx"), _) => push_unique(&mut geography.countries, "Mexico"),
+                        (Some("n"), Some("cn"), _) => push_unique(&mut geography.countries, "Canada"),
+                        _ => notes.push(format!("Geographic area code \"{}\" was not mapped.", code)),
+                    }
+                }
+                PlaceSource::Publication(place) if !has_subjects => {
+                    // `Phoenix, Ariz. :` -> `Phoenix`, `Ariz.`
+                    let place = place.trim().trim_end_matches([':', ';', ',', ' ']);
+                    let place = place.trim_start_matches('[').trim_end_matches(']');
+                    match place.split_once(", ") {
+                        Some((name, state)) => geography.add_place(&gazetteer, name, Some(state), &mut notes),
+                        None => geography.add_place(&gazetteer, place, None, &mut notes),
+                    }
+                }
+                PlaceSource::Publication(_) => {}
+            }
+        }
+
+        (geography, notes)
+    }
+
+    /// Places a name, qualified by a state or country, e.g. `Phoenix` + `Ariz.`.
+    fn add_place(&mut self, gazetteer: &[(String, Vec<String>)], name: &str, qualifier: Option<&str>, notes: &mut Vec<String>) {
+        let name = name.trim();
+        if name.is_empty() {
+            return;
+        }
+
+        // `Sonora (Mexico : State)`, `Arizona`, `Mexico`
+        if let Some(qualifier) = qualifier {
+            if let Some((country, kind)) = qualifier.split_once(" : ") {
+                if let Some(country) = country_name(country) {
+                    push_unique(&mut self.countries, country);
+                    if kind.trim() == "State" {
+                        push_unique(&mut self.states, name);
+                    }
+                    return;
+                }
+            }
+        }
+        if qualifier.is_none() {
+            if let Some(state) = state_name(name) {
+                push_unique(&mut self.states, state);
+                push_unique(&mut self.countries, "United States");
+                return;
+            }
+            if let Some(country) = country_name(name) {
+                push_unique(&mut self.countries, country);
+                return;
+            }
+        }
+
+        let state = qualifier.and_then(state_name);
+        if let Some(state) = state {
+            push_unique(&mut self.states, state);
+            push_unique(&mut self.countries, "United States");
+        } else if let Some(country) = qualifier.and_then(country_name) {
+            push_unique(&mut self.countries, country);
+        }
+
+        // `Nation` as a word, so `Grand Canyon National Park` stays a feature
+        let is_nation = name.split([' ', ',']).any(|word| word == "Nation");
+        if name.contains("Reservation") || name.contains("Indian Community") || is_nation {
+            push_unique(&mut self.tribal_homelands, name);
+            return;
+        }
+        if ROAD_WORDS.iter().any(|word| name.split_whitespace().any(|w| w == *word)) {
+            push_unique(&mut self.roads, name);
+            return;
+        }
+        if let Some(county) = name.strip_suffix(" County") {
+            push_unique(&mut self.counties, name);
+            if state == Some("Arizona") && !AZ_COUNTIES.contains(&county) {
+                notes.push(format!("\"{}\" is not an Arizona county, check the record.", name));
+            }
+            return;
+        }
+        if FEATURE_WORDS.iter().any(|word| name.ends_with(&format!(" {}", word)) || name.contains(&format!("{} of", word))) {
+            // towns like `Paradise Valley` and `Oro Valley` are in the gazetteer, features are not
+            if state != Some("Arizona") || !gazetteer.iter().any(|(town, _)| town == name) {
+                push_unique(&mut self.features, name);
+                return;
+            }
+        }
+
+        match state {
+            Some("Arizona") =>
+                match gazetteer.iter().find(|(town, _)| town == name) {
+                    Some((town, counties)) if counties.len() == 1 => {
+                        push_unique(&mut self.cities, town);
+                        push_unique(&mut self.counties, &format!("{} County", counties[0]));
+                    }
+                    Some((town, counties)) => {
+                        push_unique(&mut self.cities, town);
+                        notes.push(
+                            format!(
+                                "\"{}\" spans {} counties, County was left blank.",
+                                town,
+                                counties.join(" and ")
+                            )
+                        );
+                    }
+                    None =>
+                        notes.push(
+                            format!("\"{}\" (Ariz.) is not in the gazetteer, City or Town was left blank.", name)
+                        ),
+                }
+            // LC qualified names outside Arizona are towns unless they look like features
+            Some(_) => push_unique(&mut self.cities, name),
+            None =>
+                notes.push(format!("\"{}\" has no state or country, it was left for review.", name)),
+        }
+    }
+}
+
+/// Splits `Phoenix (Ariz.)` into `Phoenix` and `Ariz.`.
+fn split_qualifier(heading: &str) -> (&str, Option<&str>) {
+    match heading.rsplit_once(" (") {
+        Some((name, qualifier)) if qualifier.ends_with(')') =>
+            (name.trim(), Some(qualifier.trim_end_matches(')').trim())),
+        _ => (heading.trim(), None),
+    }
+}
+
+/// Expands a state name, abbreviation, or postal code, e.g. `Ariz.` or `AZ` -> `Arizona`.
+pub fn state_name(value: &str) -> Option<&'static str> {
+    let value = value.trim();
+    STATES.iter()
+        .find(|(name, postal, _, abbreviations)| {
+            *name == value ||
+                *postal == value ||
+                abbreviations.contains(&value) ||
+                // catalogers sometimes drop the period
+                abbreviations.iter().any(|a| a.trim_end_matches('.') == value && value.len() > 2)
+        })
+        .map(|(name, ..)| *name)
+}
+
+fn country_name(value: &str) -> Option<&'static str> {
+    match value.trim().trim_end_matches('.') {
+        "United States" | "U.S" | "U.S.A" => Some("United States"),
+        "Mexico" | "Mex" => Some("Mexico"),
+        "Canada" => Some("Canada"),
+        _ => None,
+    }
+}
+
+fn load_gazetteer() -> Vec<(String, Vec<String>)> {
+    include_str!("../gazetteer")
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .filter_map(|line| {
+            let (town, counties) = line.split_once('\t')?;
+            Some((
+                town.to_string(),
+                counties
+                    .split(',')
+                    .map(|c| c.trim().to_string())
+                    .collect(),
+            ))
+        })
+        .collect()
+}
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    if !values.iter().any(|v| v == value) {
+        values.push(value.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heading(heading: &str) -> (Geography, Vec<String>) {
+        Geography::from_sources(&[PlaceSource::Heading(heading.to_string())])
+    }
+
+    #[test]
+    fn splits_towns_into_city_county_and_state() {
+        let (geography, notes) = heading("Phoenix (Ariz.)");
+        assert_eq!(geography.places, ["Phoenix (Ariz.)"]);
+        assert_eq!(geography.cities, ["Phoenix"]);
+        assert_eq!(geography.counties, ["Maricopa County"]);
+        assert_eq!(geography.states, ["Arizona"]);
+        assert_eq!(geography.countries, ["United States"]);
+        assert!(notes.is_empty());
+
+        let (geography, notes) = heading("Wickenburg (Ariz.)");
+        assert_eq!(geography.cities, ["Wickenburg"]);
+        assert!(geography.counties.is_empty());
+        assert_eq!(notes, ["\"Wickenburg\" spans Maricopa and Yavapai counties, County was left blank."]);
+
+        let (geography, _) = heading("Pima County (Ariz.)");
+        assert_eq!(geography.counties, ["Pima County"]);
+        assert!(geography.cities.is_empty());
+    }
+
+    #[test]
+    fn reads_indirect_subdivisions() {
+        let sources = [PlaceSource::Subdivisions(vec!["Arizona".to_string(), "Tucson.".to_string()])];
+        let (geography, _) = Geography::from_sources(&sources);
+        assert_eq!(geography.cities, ["Tucson"]);
+        assert_eq!(geography.counties, ["Pima County"]);
+        assert_eq!(geography.states, ["Arizona"]);
+
+        let sources = [PlaceSource::Subdivisions(vec!["Arizona".to_string(), "Pima".to_string()])];
+        let (geography, notes) = Geography::from_sources(&sources);
+        assert!(geography.cities.is_empty() && geography.counties.is_empty());
+        assert_eq!(notes, ["\"Pima\" (Ariz.) could be a town or a county, it was left for review."]);
+    }
+
+    #[test]
+    fn tells_features_from_tribal_homelands() {
+        for feature in ["Grand Canyon National Park (Ariz.)", "Coconino National Forest (Ariz.)", "Salt River Valley (Ariz.)"] {
+            let (geography, _) = heading(feature);
+            assert_eq!(geography.features.len(), 1, "{}", feature);
+            assert!(geography.tribal_homelands.is_empty(), "{}", feature);
+        }
+        for homeland in [
+            "Fort Apache Indian Reservation (Ariz.)",
+            "Salt River Pima-Maricopa Indian Community (Ariz.)",
+            "Navajo Nation, Arizona, New Mexico & Utah",
+        ] {
+            let (geography, _) = heading(homeland);
+            assert_eq!(geography.tribal_homelands.len(), 1, "{}", homeland);
+            assert!(geography.features.is_empty(), "{}", homeland);
+        }
+        // a town named like a feature
+        let (geography, _) = heading("Paradise Valley (Ariz.)");
+        assert_eq!(geography.cities, ["Paradise Valley"]);
+        assert!(geography.features.is_empty());
+    }
+
+    #[test]
+    fn reads_area_codes() {
+        let sources = [PlaceSource::AreaCode("n-us-az".to_string()), PlaceSource::AreaCode("n-us-nb--".to_string())];
+        let (geography, notes) = Geography::from_sources(&sources);
+        assert_eq!(geography.states, ["Arizona", "Nebraska"]);
+        assert_eq!(geography.countries, ["United States"]);
+        assert!(notes.is_empty());
+
+        let (geography, notes) = Geography::from_sources(&[PlaceSource::AreaCode("e-fr---".to_string())]);
+        assert!(geography.countries.is_empty());
+        assert_eq!(notes, ["Geographic area code \"e-fr\" was not mapped."]);
+    }
+
+    #[test]
+    fn expands_state_names() {
+        assert_eq!(state_name("Ariz."), Some("Arizona"));
+        assert_eq!(state_name("AZ"), Some("Arizona"));
+        assert_eq!(state_name("NE"), Some("Nebraska"));
+        assert_eq!(state_name("Nebr"), Some("Nebraska"));
+        // 043 codes and lowercase words aren't postal codes
+        assert_eq!(state_name("NB"), None);
+        assert_eq!(state_name("in"), None);
+    }
+}
@@ -0,0 +1,115 @@
+/// Formats an ISSN as `1234-5679`, `None` if it doesn't have 8 characters or its check digit is wrong.
+///
+/// E.g. `0028-0836`, `00280836`, and `0028-0836 (print)` are all `0028-0836`.
+pub fn normalize_issn(value: &str) -> Option<String> {
+    let issn = issn_characters(value)?;
+    if !is_valid_issn(&issn) {
+        return None;
+    }
+    Some(format!("{}-{}", &issn[..4], &issn[4..]))
+}
+
+/// The 8 characters of an ISSN, without the hyphen or any qualifier, e.g. `0028-0836 (print)` -> `00280836`.
+fn issn_characters(value: &str) -> Option<String> {
+    let issn = value
+        .split_whitespace()
+        .next()?
+        .chars()
+        .filter(|c| *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect::<String>();
+    let is_well_formed =
+        issn.is_ascii() &&
+        issn.len() == 8 &&
+        issn[..7].chars().all(|c| c.is_ascii_digit()) &&
+        issn[7..].chars().all(|c| c.is_ascii_digit() || c == 'X');
+    if is_well_formed {
+        Some(issn)
+    } else {
+        None
+    }
+}
+
+/// Checks the last character of an ISSN, the sum of the first 7 digits weighted 8 to 2, mod 11.
+fn is_valid_issn(issn: &str) -> bool {
+    let sum = issn
+        .chars()
+        .take(7)
+        .zip((2..=8).rev())
+        .map(|(c, weight)| c.to_digit(10).unwrap_or(0) * weight)
+        .sum::<u32>();
+    let check = match (11 - (sum % 11)) % 11 {
+        10 => 'X',
+        digit => char::from_digit(digit, 10).unwrap(),
+    };
+    issn.ends_with(check)
+}
+
+/// Normalizes an LCCN by the Library of Congress rules, `None` if the result isn't a valid LCCN.
+///
+/// Blanks and anything from a `/` on are removed, and the serial number after a hyphen is left padded
+/// with zeros to 6 digits. E.g. `sn 85-1234` -> `sn85001234`, `n 78-890351 /r852` -> `n78890351`.
+pub fn normalize_lccn(value: &str) -> Option<String> {
+    let lccn = value
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    let lccn = match lccn.split_once('-') {
+        Some((year, serial)) if !serial.is_empty() && serial.chars().all(|c| c.is_ascii_digit()) =>
+            format!("{}{:0>6}", year, serial),
+        Some(_) => {
+            return None;
+        }
+        None => lccn,
+    };
+    // a prefix of up to 3 letters, then 8 digits (2 digit year) or 10 digits (4 digit year)
+    let digits = lccn.trim_start_matches(|c: char| c.is_ascii_lowercase());
+    let prefix_len = lccn.len() - digits.len();
+    let is_valid =
+        prefix_len <= 3 &&
+        (digits.len() == 8 || digits.len() == 10) &&
+        digits.chars().all(|c| c.is_ascii_digit());
+    if is_valid {
+        Some(lccn)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_issn_check_digits() {
+        assert_eq!(normalize_issn("0028-0836"), Some("0028-0836".to_string()));
+        assert_eq!(normalize_issn("00280836 (print)"), Some("0028-0836".to_string()));
+        assert_eq!(normalize_issn("0028-0837"), None);
+        // a check digit of 10 is written X
+        assert_eq!(normalize_issn("2434-561x"), Some("2434-561X".to_string()));
+        assert_eq!(normalize_issn("2434-5610"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_issns() {
+        assert_eq!(normalize_issn("0028-083"), None);
+        assert_eq!(normalize_issn("002X-0836"), None);
+        // 8 bytes, but not 8 characters
+        assert_eq!(normalize_issn("0028-08é"), None);
+        assert_eq!(normalize_issn("é0028083"), None);
+    }
+
+    #[test]
+    fn normalizes_lccns() {
+        assert_eq!(normalize_lccn("sn 85-1234"), Some("sn85001234".to_string()));
+        assert_eq!(normalize_lccn("n 78-890351 /r852"), Some("n78890351".to_string()));
+        assert_eq!(normalize_lccn("   85000002 "), Some("85000002".to_string()));
+        assert_eq!(normalize_lccn("2001-1114"), Some("2001001114".to_string()));
+        assert_eq!(normalize_lccn("sn85-"), None);
+        assert_eq!(normalize_lccn("abcd85001234"), None);
+        assert_eq!(normalize_lccn("85-12a4"), None);
+    }
+}
@@ -0,0 +1,198 @@
+use std::{ fmt::{ self, Display, Formatter }, fs::File, io::{ Read, Seek, SeekFrom }, path::{ Path, PathBuf } };
+
+use dialoguer::{ theme::ColorfulTheme, Confirm };
+use robo_archiver::ArchiveError;
+
+use crate::{ pdf::{ find, is_encrypted }, DigitalFormat };
+
+/// How much of the start and end of each file is read.
+///
+/// Trailers and cross-reference stream dictionaries live near the end of a PDF,
+/// but linearized PDFs repeat them in the first few kilobytes.
+const WINDOW: u64 = 64 * 1024;
+
+#[derive(Debug)]
+pub enum IntegrityProblem {
+    /// The file has zero bytes.
+    Empty,
+    /// The file couldn't be opened or read.
+    Unreadable(std::io::Error),
+    /// The file's content doesn't match any format, let alone the one its extension promises.
+    WrongMagic {
+        expected: DigitalFormat,
+    },
+    /// The file's content is a known format, but not the one its extension promises.
+    ///
+    /// This is only a warning, the detected format is used for the "Digital Format" column.
+    FormatMismatch {
+        extension: DigitalFormat,
+        content: DigitalFormat,
+    },
+    /// A PDF without an `%%EOF` marker, usually an interrupted copy or download.
+    MissingPdfEof,
+    /// A PDF with an `/Encrypt` dictionary. The portal can't index password-protected files.
+    EncryptedPdf,
+    /// A JPEG without an end-of-image marker.
+    TruncatedJpeg,
+    /// A TIFF whose first image directory points outside the file.
+    InvalidTiffHeader,
+}
+
+impl IntegrityProblem {
+    /// Warnings are reported, but don't keep the file from being processed.
+    pub fn is_warning(&self) -> bool {
+        matches!(self, IntegrityProblem::FormatMismatch { .. })
+    }
+}
+
+impl Display for IntegrityProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityProblem::Empty => write!(f, "the file is empty"),
+            IntegrityProblem::Unreadable(err) => write!(f, "the file could not be read ({})", err),
+            IntegrityProblem::WrongMagic { expected } =>
+                write!(f, "the file extension says {}, but the content is not {}", expected, expected),
+            IntegrityProblem::FormatMismatch { extension, content } =>
+                write!(f, "the file extension says {}, but the content is {}; {} will be used", extension, content, content),
+            IntegrityProblem::MissingPdfEof => write!(f, "the PDF has no %%EOF marker and is probably truncated"),
+            IntegrityProblem::EncryptedPdf => write!(f, "the PDF is encrypted or password-protected"),
+            IntegrityProblem::TruncatedJpeg => write!(f, "the JPEG has no end-of-image marker and is probably truncated"),
+            IntegrityProblem::InvalidTiffHeader => write!(f, "the TIFF header points outside the file"),
+        }
+    }
+}
+
+/// Checks a single file's content against its extension, then checks the content for truncation and encryption.
+pub fn check_file<P>(path: P) -> Vec<IntegrityProblem> where P: AsRef<Path> {
+    let (len, head, tail) = match read_head_and_tail(path.as_ref()) {
+        Ok(parts) => parts,
+        Err(err) => {
+            return vec![IntegrityProblem::Unreadable(err)];
+        }
+    };
+    if len == 0 {
+        return vec![IntegrityProblem::Empty];
+    }
+
+    let mut problems = Vec::new();
+    let extension = path
+        .as_ref()
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(DigitalFormat::from);
+    let content = DigitalFormat::detect(&head);
+
+    match (extension, &content) {
+        // nothing to compare against
+        (Some(DigitalFormat::Other(_)) | None, None) => {}
+        (Some(expected), None) => problems.push(IntegrityProblem::WrongMagic { expected }),
+        (Some(extension), Some(content)) if &extension != content => {
+            problems.push(IntegrityProblem::FormatMismatch { extension, content: content.clone() });
+        }
+        _ => {}
+    }
+
+    match content {
+        Some(DigitalFormat::PDF) => {
+            if find(&tail, b"%%EOF", 0).is_none() {
+                problems.push(IntegrityProblem::MissingPdfEof);
+            }
+            match File::open(path.as_ref()).and_then(|mut file| is_encrypted(&mut file)) {
+                Ok(true) => problems.push(IntegrityProblem::EncryptedPdf),
+                Ok(false) => {}
+                Err(err) => problems.push(IntegrityProblem::Unreadable(err)),
+            }
+        }
+        Some(DigitalFormat::JPEG) => {
+            // some scanners pad the file after the end-of-image marker
+            let has_eoi = tail
+                .iter()
+                .rposition(|b| *b != 0)
+                .is_some_and(|end| end >= 1 && tail[end - 1..=end] == [0xff, 0xd9]);
+            if !has_eoi {
+                problems.push(IntegrityProblem::TruncatedJpeg);
+            }
+        }
+        Some(DigitalFormat::TIFF) => {
+            let offset = match &head[..8.min(head.len())] {
+                [b'I', b'I', 42, 0, rest @ ..] if rest.len() == 4 =>
+                    u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]),
+                [b'M', b'M', 0, 42, rest @ ..] if rest.len() == 4 =>
+                    u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]),
+                _ => 0,
+            };
+            // the directory needs at least its 2-byte entry count
+            if offset < 8 || (offset as u64) + 2 > len {
+                problems.push(IntegrityProblem::InvalidTiffHeader);
+            }
+        }
+        _ => {}
+    }
+    problems
+}
+
+/// Runs [`check_file`] over every file, reports the problems per file, and returns the files that passed.
+///
+/// Files with only warnings pass. If any file fails, the operator decides whether to continue without it.
+pub fn check_integrity(file_paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, ArchiveError> {
+    let mut passed = Vec::new();
+    let mut warned = Vec::new();
+    let mut failed = Vec::new();
+    for path in file_paths {
+        let problems = check_file(&path);
+        if problems.is_empty() {
+            passed.push(path);
+        } else if problems.iter().all(IntegrityProblem::is_warning) {
+            passed.push(path.clone());
+            warned.push((path, problems));
+        } else {
+            failed.push((path, problems));
+        }
+    }
+
+    if !warned.is_empty() {
+        eprintln!("[WARN] {} file(s) have an extension that doesn't match their content:", warned.len());
+        report(&warned);
+    }
+
+    if failed.is_empty() {
+        return Ok(passed);
+    }
+
+    eprintln!("[WARN] {} file(s) failed the integrity check:", failed.len());
+    report(&failed);
+
+    let proceed = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Continue without these files?")
+        .default(true)
+        .interact()
+        .unwrap();
+    if proceed {
+        Ok(passed)
+    } else {
+        Err(ArchiveError::IntegrityCheckFailed(failed.len()))
+    }
+}
+
+fn report(files: &[(PathBuf, Vec<IntegrityProblem>)]) {
+    for (path, problems) in files {
+        eprintln!("  {:?}", path);
+        for problem in problems {
+            eprintln!("    - {}", problem);
+        }
+    }
+}
+
+fn read_head_and_tail(path: &Path) -> std::io::Result<(u64, Vec<u8>, Vec<u8>)> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+
+    let mut head = Vec::new();
+    (&mut file).take(WINDOW).read_to_end(&mut head)?;
+
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(len.saturating_sub(WINDOW)))?;
+    file.read_to_end(&mut tail)?;
+
+    Ok((len, head, tail))
+}
@@ -0,0 +1,146 @@
+/// ISO 639-2 bibliographic codes and the language names used by the portal.
+///
+/// The codes cover the languages found in Arizona periodicals, plus the most common languages of other
+/// collections. Codes missing from the table are noted for review rather than guessed.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("apa", "Apache"),
+    ("ara", "Arabic"),
+    ("arm", "Armenian"),
+    ("baq", "Basque"),
+    ("chi", "Chinese"),
+    ("cze", "Czech"),
+    ("dan", "Danish"),
+    ("dut", "Dutch"),
+    ("eng", "English"),
+    ("epo", "Esperanto"),
+    ("est", "Estonian"),
+    ("fin", "Finnish"),
+    ("fre", "French"),
+    ("ger", "German"),
+    ("gre", "Greek"),
+    ("grc", "Greek, Ancient"),
+    ("haw", "Hawaiian"),
+    ("heb", "Hebrew"),
+    ("hin", "Hindi"),
+    ("hop", "Hopi"),
+    ("hun", "Hungarian"),
+    ("ice", "Icelandic"),
+    ("ind", "Indonesian"),
+    ("iri", "Irish"),
+    ("gle", "Irish"),
+    ("ita", "Italian"),
+    ("jpn", "Japanese"),
+    ("kor", "Korean"),
+    ("lat", "Latin"),
+    ("lav", "Latvian"),
+    ("lit", "Lithuanian"),
+    ("mah", "Marshallese"),
+    ("may", "Malay"),
+    ("nah", "Nahuatl"),
+    ("nai", "North American Indian"),
+    ("nav", "Navajo"),
+    ("nor", "Norwegian"),
+    ("per", "Persian"),
+    ("pol", "Polish"),
+    ("por", "Portuguese"),
+    ("rum", "Romanian"),
+    ("rus", "Russian"),
+    ("sco", "Scots"),
+    ("scr", "Croatian"),
+    ("scc", "Serbian"),
+    ("slo", "Slovak"),
+    ("slv", "Slovenian"),
+    ("spa", "Spanish"),
+    ("swe", "Swedish"),
+    ("tag", "Tagalog"),
+    ("tgl", "Tagalog"),
+    ("tha", "Thai"),
+    ("tur", "Turkish"),
+    ("ukr", "Ukrainian"),
+    ("urd", "Urdu"),
+    ("vie", "Vietnamese"),
+    ("wel", "Welsh"),
+    ("yid", "Yiddish"),
+    ("zun", "Zuni"),
+    ("chr", "Cherokee"),
+    ("sgn", "Sign languages"),
+];
+
+/// Codes that say something about the record, but don't name a language.
+///
+/// `mul` (multiple), `und` (undetermined), `zxx` (no linguistic content), and blanks.
+const NOT_A_LANGUAGE: [&str; 5] = ["mul", "und", "zxx", "   ", "|||"];
+
+/// Looks up the portal's name for an ISO 639-2 code, e.g. `spa -> Spanish`.
+pub fn language_name(code: &str) -> Option<&'static str> {
+    let code = code.trim().to_lowercase();
+    LANGUAGES.iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+}
+
+/// Whether the code names a language, as opposed to `mul`, `und`, or `zxx`.
+pub fn is_language_code(code: &str) -> bool {
+    code.len() == 3 && !NOT_A_LANGUAGE.contains(&code.to_lowercase().as_str())
+}
+
+/// Splits a 041 subfield into codes. Older records run the codes together, e.g. `engspa`.
+pub fn split_language_codes(value: &str) -> Vec<String> {
+    let value = value.trim().trim_end_matches(|c: char| !c.is_alphabetic());
+    value
+        .chars()
+        .collect::<Vec<_>>()
+        .chunks(3)
+        .filter(|chunk| chunk.len() == 3)
+        .map(|chunk| chunk.iter().collect::<String>().to_lowercase())
+        .collect()
+}
+
+/// The languages for the issues: the record's, or the ones given on the command line when the record
+/// names none or `override_languages` is set.
+pub fn issue_languages(marc_languages: &[String], cli_languages: &[String], override_languages: bool) -> Vec<String> {
+    if override_languages || marc_languages.is_empty() {
+        cli_languages.to_vec()
+    } else {
+        marc_languages.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_run_together_codes() {
+        assert_eq!(split_language_codes("engspa"), ["eng", "spa"]);
+        assert_eq!(split_language_codes(" ENG "), ["eng"]);
+        assert_eq!(split_language_codes("engnav."), ["eng", "nav"]);
+        // a trailing partial code is dropped
+        assert_eq!(split_language_codes("engsp"), ["eng"]);
+        assert!(split_language_codes("").is_empty());
+    }
+
+    #[test]
+    fn multiple_undetermined_and_no_language_are_not_languages() {
+        for code in ["mul", "und", "zxx", "MUL", "   ", "|||"] {
+            assert!(!is_language_code(code), "{:?}", code);
+        }
+        assert!(is_language_code("nav"));
+        assert!(!is_language_code("en"));
+        assert_eq!(language_name("NAV"), Some("Navajo"));
+        assert_eq!(language_name("xyz"), None);
+    }
+
+    #[test]
+    fn falls_back_to_the_command_line_languages() {
+        let marc = strings(&["Spanish", "English"]);
+        let cli = strings(&["English"]);
+        assert_eq!(issue_languages(&marc, &cli, false), marc);
+        assert_eq!(issue_languages(&[], &cli, false), cli);
+        assert_eq!(issue_languages(&marc, &cli, true), cli);
+    }
+}
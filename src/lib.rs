@@ -5,8 +5,22 @@ use derive_more::From;
 pub enum ArchiveError {
     Io(std::io::Error),
     Csv(csv::Error),
+    Config(toml::de::Error),
+    Json(serde_json::Error),
     Unimplemented,
     UnparseableFileName(String),
+    #[from(ignore)]
+    Pdf(String),
+    #[from(ignore)]
+    IntegrityCheckFailed(usize),
+    #[from(ignore)]
+    DuplicateIssues(String),
+    #[from(ignore)]
+    InvalidFixedField(String),
+    #[from(ignore)]
+    InvalidMarc(String),
+    #[from(ignore)]
+    Catalog(String),
 }
 
 impl std::fmt::Display for ArchiveError {
@@ -16,6 +30,15 @@ impl std::fmt::Display for ArchiveError {
             ArchiveError::Unimplemented => write!(f, "Unimplemented"),
             ArchiveError::UnparseableFileName(file) => write!(f, "Unparseable file name: {}", file),
             ArchiveError::Csv(err) => write!(f, "CSV error: {}", err),
+            ArchiveError::Config(err) => write!(f, "Config error: {}", err),
+            ArchiveError::Json(err) => write!(f, "JSON error: {}", err),
+            ArchiveError::Pdf(message) => write!(f, "PDF error: {}", message),
+            ArchiveError::IntegrityCheckFailed(count) =>
+                write!(f, "{} file(s) failed the integrity check", count),
+            ArchiveError::DuplicateIssues(set) => write!(f, "Duplicate issues: {}", set),
+            ArchiveError::InvalidFixedField(message) => write!(f, "Invalid 008: {}", message),
+            ArchiveError::InvalidMarc(message) => write!(f, "Invalid MARC record: {}", message),
+            ArchiveError::Catalog(message) => write!(f, "Catalog error: {}", message),
         }
     }
 }
@@ -1,24 +1,55 @@
+pub mod agencies;
+pub mod cache;
+pub mod catalog;
 pub mod cli;
+pub mod config;
+pub mod contributors;
 pub mod data;
+pub mod description;
+pub mod duplicates;
 pub mod files;
+pub mod fixed_field;
+pub mod gaps;
+pub mod identifiers;
+pub mod geography;
+pub mod integrity;
+pub mod languages;
 pub mod marc;
+pub mod marc8;
+pub mod pdf;
 pub mod save;
+pub mod staff_view;
+pub mod titles;
 pub mod topics;
 
+use std::collections::HashMap;
+
 use clap::Parser;
+use dialoguer::{ theme::ColorfulTheme, Confirm, Input, MultiSelect, Select };
 use files::{ load_directory, process_files };
 use robo_archiver::ArchiveError;
 use crate::{
+    cache::{ CachedRecord, MarcCache },
+    catalog::{ search_input, Catalog, SearchOutcome, SruCatalog },
     cli::Args,
+    config::{ load_config, Config },
+    contributors::suggest_contributors,
     data::*,
-    marc::{ accept_marc, parse_marc },
+    description::propose_description,
+    gaps::{ expected_frequency, find_gaps },
+    integrity::check_integrity,
+    languages::issue_languages,
+    marc::{ accept_marc, read_marc, Record },
+    pdf::{ inspect_pdf, suggest_volume_issue, Confidence },
     save::write_periodicals_to_file,
+    titles::{ is_similar_title, title_changes, with_marc_title, TitleChange },
     topics::select_topics_with_retries,
 };
 
 fn main() -> Result<(), ArchiveError> {
     // Parse command line arguments.
     let args = Args::parse();
+    let config = load_config(args.config)?;
     let languages = args.languages.unwrap_or_else(|| vec!["English".to_string()]);
     let file_exts = args.file_ext;
     let out_path = args.out_path;
@@ -30,20 +61,28 @@ fn main() -> Result<(), ArchiveError> {
     let digitizing_instituion = args.digitization_institution
         .map(DigitizingInstitution::from)
         .unwrap_or_default();
+    let inspect_pdfs = args.inspect_pdfs;
+    let marc_titles = args.marc_titles;
+    let override_languages = args.override_languages;
 
     let periodicals = {
         let path = args.file_dir.unwrap_or_else(|| ".".to_string());
         let is_recursive = args.recursive;
         let file_paths = load_directory(path, is_recursive, file_exts);
-        let data = process_files(file_paths)?;
-        process_periodicals(
-            data,
+        let file_paths = check_integrity(file_paths)?;
+        let data = process_files(file_paths, args.duplicates)?;
+        let options = RunOptions {
             languages,
+            override_languages,
             digitizing_instituion,
             rights_statement,
             collection,
-            contributing_institution
-        )?
+            contributing_institution,
+            inspect_pdfs,
+            marc_titles,
+            config,
+        };
+        process_periodicals(data, &options)?
     };
 
     write_periodicals_to_file(periodicals, out_path)?;
@@ -51,44 +90,350 @@ fn main() -> Result<(), ArchiveError> {
     Ok(())
 }
 
-fn prompt_marc(collection_name: &str) -> MarcData {
-    let call_number = {
-        let input = prompt_user_input(
-            format!("Enter the call number (or just hit [ENTER] if PERIODICAL) for the \"{}\" collection:", collection_name).as_str()
-        );
-        if input.is_empty() {
-            CallNumber::Periodical
-        } else {
-            CallNumber::Shelf(input)
+/// Asks for the MARC record, unless a cached record is reused.
+///
+/// With a catalog, the record is looked up first, and pasted when the search finds nothing.
+/// Returns the record's fields too, so they can be cached.
+fn prompt_marc(
+    collection_name: &str,
+    config: &Config,
+    cached: Option<&CachedRecord>,
+    catalog: Option<&dyn Catalog>
+) -> (MarcData, Vec<Record>) {
+    let mut records = match (cached, catalog) {
+        (Some(cached), _) => cached.marc.clone(),
+        (None, Some(catalog)) => search_catalog(catalog, collection_name).unwrap_or_else(paste_marc),
+        (None, None) => paste_marc(),
+    };
+    loop {
+        match MarcData::try_from_records(records.clone(), config) {
+            Ok(marc) => {
+                return (marc, records);
+            }
+            Err(err) => {
+                eprintln!("[WARN] The MARC record could not be parsed, paste it again: {}", err);
+                records = paste_marc();
+            }
+        }
+    }
+}
+
+/// Asks for the record to be pasted, or a file entered, until it can be read.
+fn paste_marc() -> Vec<Record> {
+    loop {
+        match accept_marc().and_then(|marc| read_marc(&marc)) {
+            Ok(records) => {
+                return records;
+            }
+            Err(err) => {
+                eprintln!("[WARN] The MARC record could not be read, paste it again: {}", err);
+            }
+        }
+    }
+}
+
+/// Searches the catalog by OCLC number, ISSN, LCCN, or title, and asks which record to use.
+///
+/// `None` when the search is skipped, fails, or finds nothing, so the record can be pasted instead.
+fn search_catalog(catalog: &dyn Catalog, collection_name: &str) -> Option<Vec<Record>> {
+    loop {
+        let input: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt(
+                format!(
+                    "Search the catalog by OCLC number, ISSN, LCCN, or title for the \"{}\" collection (empty to paste)",
+                    collection_name
+                )
+            )
+            .with_initial_text(collection_name)
+            .allow_empty(true)
+            .interact_text()
+            .unwrap();
+        let mut records = match search_input(catalog, &input) {
+            SearchOutcome::Skipped => {
+                return None;
+            }
+            SearchOutcome::Failed(err) => {
+                eprintln!("[WARN] The catalog search failed, paste the record instead: {}", err);
+                return None;
+            }
+            SearchOutcome::NotFound(query) => {
+                eprintln!("[WARN] Nothing was found for {:?}.", query);
+                continue;
+            }
+            SearchOutcome::Found(records) => records,
+        };
+        let mut items = records
+            .iter()
+            .map(|record| record.title.clone())
+            .collect::<Vec<_>>();
+        items.push("Search again".to_string());
+        items.push("Paste a record instead".to_string());
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Select the record for the \"{}\" collection", collection_name))
+            .items(&items)
+            .default(0)
+            .interact()
+            .unwrap();
+        match selection {
+            i if i < records.len() => {
+                return Some(records.swap_remove(i).marc);
+            }
+            i if i == records.len() => {
+                continue;
+            }
+            _ => {
+                return None;
+            }
+        }
+    }
+}
+
+/// Offers to reuse the cached record for a title, with the call number, description, and topics given for it.
+fn prompt_cached_record(cache: &MarcCache, collection_name: &str) -> Option<CachedRecord> {
+    let cached = cache.find(collection_name)?;
+    let oclc_number = if cached.oclc_number.is_empty() {
+        String::new()
+    } else {
+        format!(" (OCLC {})", cached.oclc_number)
+    };
+    let reuse = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(
+            format!(
+                "Reuse the cached record \"{}\"{} for the \"{}\" collection?",
+                cached.titles.first().map(String::as_str).unwrap_or_default(),
+                oclc_number,
+                collection_name
+            )
+        )
+        .default(true)
+        .interact()
+        .unwrap();
+    if reuse {
+        Some(cached)
+    } else {
+        None
+    }
+}
+
+/// Offers the call number, description, and topics of a record cached under another title, when the
+/// record has the same OCLC number, ISSN, or LCCN.
+fn prompt_matching_record(cache: &MarcCache, collection_name: &str, match_keys: &[String]) -> Option<CachedRecord> {
+    let cached = cache.find_by_keys(match_keys)?;
+    let key = cached.match_keys
+        .iter()
+        .find(|key| match_keys.contains(key))
+        .cloned()
+        .unwrap_or_default();
+    let reuse = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(
+            format!(
+                "The record for the \"{}\" collection has the same {} as the cached record \"{}\", reuse its call number, description, and topics?",
+                collection_name,
+                key,
+                cached.titles.first().map(String::as_str).unwrap_or_default()
+            )
+        )
+        .default(true)
+        .interact()
+        .unwrap();
+    if reuse {
+        Some(cached)
+    } else {
+        None
+    }
+}
+
+/// Asks for the call number, with the one found in the MARC record as the default.
+fn prompt_call_number(collection_name: &str, proposed: &CallNumber) -> CallNumber {
+    let default = match proposed {
+        CallNumber::Periodical => "PERIODICAL".to_string(),
+        CallNumber::Shelf(s) => s.clone(),
+    };
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(
+            format!("Enter the call number (or PERIODICAL) for the \"{}\" collection", collection_name)
+        )
+        .default(default)
+        .interact_text()
+        .unwrap();
+    let input = input.trim();
+    if input.is_empty() || input.eq_ignore_ascii_case("PERIODICAL") {
+        CallNumber::Periodical
+    } else {
+        CallNumber::Shelf(input.to_string())
+    }
+}
+
+/// Warns when the file name title doesn't resemble any MARC title, which usually means the wrong record was pasted.
+fn check_marc_title(file_title: &str, marc: &mut MarcData) {
+    let titles = marc.title
+        .iter()
+        .chain(marc.title_proper.iter())
+        .chain(marc.variant_titles.iter())
+        .collect::<Vec<_>>();
+    if titles.is_empty() || titles.iter().any(|title| is_similar_title(file_title, title)) {
+        return;
+    }
+    let note = format!(
+        "The file name title \"{}\" doesn't match the MARC title \"{}\", check that the right record was pasted.",
+        file_title,
+        titles[0]
+    );
+    eprintln!("[WARN] {}", note);
+    marc.review_notes.push(note);
+}
+
+/// Offers the volume and issue numbers found in a PDF's text layer for confirmation.
+fn prompt_volume_issue(issue_data: &IssueFileData) -> (Option<String>, Option<IssueNo>) {
+    if !matches!(issue_data.format, DigitalFormat::PDF) {
+        return (None, None);
+    }
+    let pdf = match inspect_pdf(&issue_data.path) {
+        Ok(pdf) => pdf,
+        Err(err) => {
+            eprintln!("[WARN] Could not inspect {:?}: {}", issue_data.path, err);
+            return (None, None);
         }
     };
-    let marc = accept_marc();
-    let marc = std::io::BufReader::new(marc.as_bytes());
-    parse_marc(call_number, marc).unwrap()
+    let Some(suggestion) = suggest_volume_issue(&pdf) else {
+        return (None, None);
+    };
+    let accepted = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(
+            format!("Use {} for \"{}\"?", suggestion, issue_data.node_title_with_date())
+        )
+        .default(suggestion.confidence == Confidence::High)
+        .interact()
+        .unwrap();
+    if accepted {
+        (suggestion.volume_no, suggestion.issue_no.map(IssueNo::Number))
+    } else {
+        (None, None)
+    }
 }
 
-fn prompt_user_input(prompt: &str) -> String {
-    println!("{}", prompt);
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
+/// Offers the contributors found in the record's credits and notes, all of them checked.
+fn prompt_contributors(collection_name: &str, marc: &MarcData) -> Vec<String> {
+    let statements = marc.credits
+        .iter()
+        .chain(marc.general_notes.iter())
+        .cloned()
+        .collect::<Vec<_>>();
+    let candidates = suggest_contributors(&statements);
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+    if candidates.len() > 7 {
+        eprintln!(
+            "[WARN] {} contributors were found, lists longer than 7 should be reviewed with a Collective Librarian.",
+            candidates.len()
+        );
+    }
+    let selected = MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Select the contributors of the \"{}\" collection", collection_name))
+        .items(&candidates)
+        .defaults(&vec![true; candidates.len()])
+        .interact()
+        .unwrap();
+    selected
+        .into_iter()
+        .map(|i| candidates[i].clone())
+        .collect()
+}
+
+/// Asks for the description of a periodical, starting from the one proposed from its MARC record.
+fn prompt_description(parent_collection: &str, proposed: &str) -> String {
+    let input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Edit the description of the \"{}\" periodical", parent_collection))
+        .with_initial_text(proposed)
+        .allow_empty(true)
+        .interact_text()
+        .unwrap();
     input.trim().to_string()
 }
 
-fn process_periodicals(
-    data: Vec<Vec<IssueFileData>>,
+/// Settings from the command line and config file that apply to every periodical in the run.
+struct RunOptions {
+    /// Used when the MARC record has no languages, or always when `override_languages` is set.
     languages: Vec<String>,
+    override_languages: bool,
     digitizing_instituion: DigitizingInstitution,
     rights_statement: RightsStatement,
     collection: PeriodicalCollection,
-    contributing_institution: ContributingInstitution
+    contributing_institution: ContributingInstitution,
+    inspect_pdfs: bool,
+    marc_titles: bool,
+    config: Config,
+}
+
+fn process_periodicals(
+    data: Vec<Vec<IssueFileData>>,
+    options: &RunOptions
 ) -> Result<Vec<Periodical>, ArchiveError> {
     let mut periodicals: Vec<Periodical> = Vec::new();
+    let cache = MarcCache::open(&options.config.cache_dir);
+    let catalog = options.config.sru.clone().map(SruCatalog::new);
+    // the groups each OCLC number, ISSN, and LCCN was pasted for
+    let mut match_keys: HashMap<String, String> = HashMap::new();
     for issue_datas in data.iter() {
         let periodical_collection = issue_datas
             .first()
             .expect("Expected periodical to have at least one issue")
             .node_title.clone();
-        let marc = prompt_marc(periodical_collection.as_str());
+        let cached = cache.as_ref().and_then(|cache| prompt_cached_record(cache, &periodical_collection));
+        let (mut marc, marc_records) = prompt_marc(
+            periodical_collection.as_str(),
+            &options.config,
+            cached.as_ref(),
+            catalog.as_ref().map(|catalog| catalog as &dyn Catalog)
+        );
+        let cached = cached.or_else(|| {
+            cache.as_ref().and_then(|cache| prompt_matching_record(cache, &periodical_collection, &marc.match_keys()))
+        });
+        let proposed = cached.as_ref().map_or(&marc.call_number, |cached| &cached.call_number);
+        marc.call_number = prompt_call_number(&periodical_collection, proposed);
+        check_marc_title(&periodical_collection, &mut marc);
+        for key in marc.match_keys() {
+            match match_keys.get(&key) {
+                Some(other) if *other != periodical_collection => {
+                    let note = format!(
+                        "The record for \"{}\" has the same {} as \"{}\", check that the right record was pasted.",
+                        periodical_collection,
+                        key,
+                        other
+                    );
+                    eprintln!("[WARN] {}", note);
+                    marc.review_notes.push(note);
+                }
+                Some(_) => {}
+                None => {
+                    match_keys.insert(key, periodical_collection.clone());
+                }
+            }
+        }
+        // with `--marc-titles`, the issues take the title from 245 instead of the file names
+        let marc_title = marc.title.as_deref().filter(|_| options.marc_titles);
+        let issue_datas = &with_marc_title(issue_datas, marc_title);
+        let gaps = match expected_frequency(issue_datas, &marc) {
+            Some(expected) => {
+                let gaps = find_gaps(issue_datas, &expected);
+                if !gaps.is_empty() {
+                    eprintln!(
+                        "[WARN] \"{}\" is expected to be {}, but {} gap(s) were found:",
+                        periodical_collection,
+                        expected,
+                        gaps.len()
+                    );
+                    for gap in &gaps {
+                        eprintln!("  - {}", gap);
+                    }
+                }
+                gaps
+            }
+            None => Vec::new(),
+        };
+        let languages = issue_languages(&marc.languages, &options.languages, options.override_languages);
+        let contributors = prompt_contributors(&periodical_collection, &marc);
         let mut issues: Vec<Issue> = Vec::new();
 
         for (i, issue_data) in issue_datas.iter().enumerate() {
@@ -98,6 +443,18 @@ fn process_periodicals(
                 issue_datas.get(i - 1).map(|i| { i.node_title_with_date() })
             };
             let next_issue = issue_datas.get(i + 1).map(|i| { i.node_title_with_date() });
+            let (volume_no, issue_no) = if options.inspect_pdfs {
+                prompt_volume_issue(issue_data)
+            } else {
+                (None, None)
+            };
+
+            // note the gap on the issue whose "Next Issue" link skips over it
+            let admin_notes = gaps
+                .iter()
+                .filter(|gap| gap.after == issue_data.date_original)
+                .map(|gap| format!("Gap: {}", gap))
+                .collect();
 
             let issue = Issue {
                 marc: marc.clone(),
@@ -108,31 +465,158 @@ fn process_periodicals(
                 parent_collection: issue_data.node_title.clone(),
                 next_issue,
                 previous_issue,
-                contributors: Vec::new(),
+                contributors: contributors.clone(),
                 languages: languages.clone(),
-                issue_no: None,
-                volume_no: None,
+                issue_no,
+                volume_no,
                 item_type: IssueType::Text,
                 format_type: IssueFormatType::Periodical,
-                digitizing_institution: digitizing_instituion.clone(),
-                rights_statement: rights_statement.clone(),
+                digitizing_institution: options.digitizing_instituion.clone(),
+                rights_statement: options.rights_statement.clone(),
+                admin_notes,
             };
             issues.push(issue);
         }
 
         let parent_collection = issues.first().unwrap().parent_collection.clone();
+        if contributors.len() > 7 {
+            issues[0].admin_notes.push(
+                format!("{} contributors, review them with a Collective Librarian.", contributors.len())
+            );
+        }
 
+        let proposed_description = match &cached {
+            Some(cached) => cached.description.clone(),
+            None => propose_description(&options.config.description_template, &parent_collection, &marc, issue_datas),
+        };
+        let cached_topics = cached.as_ref().map(|cached| cached.topics.clone()).unwrap_or_default();
         let periodical = Periodical {
-            description: prompt_user_input(
-                format!("Enter the description of the \"{}\" periodical:", parent_collection).as_str()
-            ),
-            collection: collection.clone(),
-            contributing_institution: contributing_institution.clone(),
+            description: prompt_description(&parent_collection, &proposed_description),
+            collection: options.collection.clone(),
+            contributing_institution: options.contributing_institution.clone(),
             issues,
-            topics: select_topics_with_retries(&parent_collection),
+            topics: select_topics_with_retries(&parent_collection, &cached_topics),
         };
 
+        // records are cached by their OCLC number, so records without one aren't
+        if let (Some(_), None) = (&cache, &marc.oclc_number) {
+            eprintln!("[WARN] The record for \"{}\" has no OCLC number, so it isn't cached.", periodical_collection);
+        }
+        if let (Some(cache), Some(oclc_number)) = (&cache, &marc.oclc_number) {
+            let titles = [&periodical_collection]
+                .into_iter()
+                .chain(marc.title.iter())
+                .chain(marc.variant_titles.iter())
+                .fold(Vec::<String>::new(), |mut titles, title| {
+                    if !titles.contains(title) {
+                        titles.push(title.clone());
+                    }
+                    titles
+                });
+            let record = CachedRecord {
+                oclc_number: oclc_number.clone(),
+                titles,
+                match_keys: marc.match_keys(),
+                marc: marc_records,
+                call_number: marc.call_number.clone(),
+                description: periodical.description.clone(),
+                topics: periodical.topics.clone(),
+            };
+            if let Err(err) = cache.save(&record) {
+                eprintln!("[WARN] Could not cache the record for \"{}\": {}", periodical_collection, err);
+            }
+        }
+
         periodicals.push(periodical);
     }
+    link_title_changes(&mut periodicals);
     Ok(periodicals)
 }
+
+/// Offers to link the last issue of a title to the first issue of the title it changed to.
+///
+/// A pair is found from the 785 of the earlier title or the 780 of the later one, by OCLC number, ISSN, or a
+/// similar title. Accepted links fill "Next Issue" and "Previous Issue" and are noted in "Admin Notes". An issue
+/// that is already linked isn't linked again.
+fn link_title_changes(periodicals: &mut [Periodical]) {
+    // (index in periodicals, (title, record)) of each periodical with issues
+    let (indices, firsts): (Vec<usize>, Vec<(&str, &MarcData)>) = periodicals
+        .iter()
+        .enumerate()
+        .filter_map(|(i, periodical)| periodical.issues.first().map(|issue| (i, issue)))
+        .map(|(i, issue)| (i, (issue.parent_collection.as_str(), &issue.marc)))
+        .unzip();
+    let changes = title_changes(&firsts);
+
+    for TitleChange { earlier, later, forward, backward, .. } in changes {
+        let (i, j) = (indices[earlier], indices[later]);
+        let earlier_title = periodicals[i].issues[0].parent_collection.clone();
+        let later_title = periodicals[j].issues[0].parent_collection.clone();
+        let last = periodicals[i].issues.last().unwrap().node_title.clone();
+        let first = periodicals[j].issues[0].node_title.clone();
+        if let Some(next) = &periodicals[i].issues.last().unwrap().next_issue {
+            eprintln!("[WARN] \"{}\" is already linked to \"{}\", so it wasn't linked to \"{}\".", last, next, first);
+            continue;
+        }
+        if let Some(previous) = &periodicals[j].issues[0].previous_issue {
+            eprintln!("[WARN] \"{}\" is already linked from \"{}\", so it wasn't linked from \"{}\".", first, previous, last);
+            continue;
+        }
+        let accepted = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(
+                format!(
+                    "The MARC record says \"{}\" is {} \"{}\". Link \"{}\" to \"{}\"?",
+                    earlier_title,
+                    forward.to_lowercase(),
+                    later_title,
+                    last,
+                    first
+                )
+            )
+            .default(true)
+            .interact()
+            .unwrap();
+        if !accepted {
+            continue;
+        }
+
+        let last_issue = periodicals[i].issues.last_mut().unwrap();
+        last_issue.next_issue = Some(first);
+        last_issue.admin_notes.push(format!("{}: {}", forward, later_title));
+        let first_issue = periodicals[j].issues.first_mut().unwrap();
+        first_issue.previous_issue = Some(last);
+        first_issue.admin_notes.push(format!("{}: {}", backward, earlier_title));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{ CatalogQuery, CatalogRecord };
+
+    /// A catalog that fails the test if it's searched.
+    struct OfflineCatalog;
+
+    impl Catalog for OfflineCatalog {
+        fn search(&self, query: &CatalogQuery) -> Result<Vec<CatalogRecord>, ArchiveError> {
+            panic!("the catalog was searched for {:?}", query);
+        }
+    }
+
+    #[test]
+    fn cached_records_are_used_without_searching_the_catalog() {
+        let cached = CachedRecord {
+            oclc_number: "000012345".to_string(),
+            titles: vec!["Arizona Highways".to_string()],
+            match_keys: vec!["oclc:000012345".to_string()],
+            marc: read_marc(b"035\t\t$a(OCoLC)12345\n245\t00\t$aArizona highways.").unwrap(),
+            call_number: CallNumber::Periodical,
+            description: String::new(),
+            topics: Vec::new(),
+        };
+        let (marc, records) = prompt_marc("Arizona Highways", &Config::default(), Some(&cached), Some(&OfflineCatalog));
+        assert_eq!(marc.oclc_number.as_deref(), Some("000012345"));
+        assert_eq!(marc.title.as_deref(), Some("Arizona highways"));
+        assert_eq!(records.len(), 2);
+    }
+}
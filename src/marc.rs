@@ -1,188 +1,1630 @@
 use std::io::BufRead;
 
 use robo_archiver::ArchiveError;
-use crate::data::{ MarcData, CallNumber };
+use serde::{ Deserialize, Serialize };
+use unicode_normalization::UnicodeNormalization;
+use crate::{
+    agencies::{ GovernmentAgencies, GovernmentLevel },
+    config::Config,
+    data::{ MarcData, CallNumber, LinkedTitle, PublisherStatement },
+    fixed_field::FixedField,
+    geography::{ Geography, PlaceSource },
+    identifiers::{ normalize_issn, normalize_lccn },
+    marc8::decode_marc8,
+    staff_view::{ is_html, staff_view_marc },
+    languages::{ is_language_code, language_name, split_language_codes },
+};
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Subfield {
     code: char,
     value: String,
 }
 
-#[derive(Debug)]
+impl Subfield {
+    pub fn new(code: char, value: &str) -> Self {
+        Self { code, value: value.to_string() }
+    }
+}
+
+/// A field of a MARC record. Control fields have a single subfield coded `_`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Record {
     tag: String,
     ind: String,
     subfields: Vec<Subfield>,
 }
 
+/// Tags whose first indicator is usually blank, so a lone indicator in the catalog is the second one.
+const SECOND_INDICATOR_TAGS: [&str; 4] = ["264", "650", "651", "655"];
+
+/// Thesauri in `$2` that count as Library of Congress headings when the 2nd indicator is 7.
+const LC_THESAURI: [&str; 3] = ["lcsh", "lcgft", "lctgm"];
+
+impl Record {
+    /// A control field, e.g. `001` with `ocm00012345`.
+    pub fn control_field(tag: &str, value: &str) -> Self {
+        Self {
+            tag: tag.to_string(),
+            ind: String::new(),
+            subfields: vec![Subfield::new('_', value)],
+        }
+    }
+
+    /// A data field, e.g. `245` with indicators `03` and its subfields.
+    pub fn data_field(tag: &str, ind: &str, subfields: Vec<Subfield>) -> Self {
+        Self { tag: tag.to_string(), ind: ind.to_string(), subfields }
+    }
+
+    /// The first and second indicators, with blanks as `' '`.
+    ///
+    /// The catalog trims the indicator column, so `" 0"` (650) and `"1 "` (700) display as `"0"` and `"1"`.
+    /// A lone indicator is read as the second one for [`SECOND_INDICATOR_TAGS`], and as the first otherwise.
+    fn indicators(&self) -> (char, char) {
+        let chars = self.ind
+            .chars()
+            .map(|c| if c == '#' || c == '_' { ' ' } else { c })
+            .collect::<Vec<_>>();
+        match chars.as_slice() {
+            [] => (' ', ' '),
+            [one] if SECOND_INDICATOR_TAGS.contains(&self.tag.as_str()) => (' ', *one),
+            [one] => (*one, ' '),
+            [first, second, ..] => (*first, *second),
+        }
+    }
+}
+
 /// This function prompts a user to input a string, which should be a valid MARC record with line breaks.
 /// After the user pastes the MARC record, and submits an empty line, the function returns the input.
-pub fn accept_marc() -> String {
-    println!("Paste a MARC record:");
-    let mut marc = String::new();
-    for line in std::io::stdin().lock().lines() {
-        let line = line.unwrap();
+///
+/// The input is kept as bytes, since older records are MARC-8 rather than UTF-8. A path to a binary
+/// `.mrc` file or a saved staff view `.html` page may be entered instead of pasting, and the file's
+/// contents are returned, or the error reading it.
+pub fn accept_marc() -> Result<Vec<u8>, ArchiveError> {
+    println!("Paste a MARC record, or enter the path to a .mrc file or a saved staff view .html page:");
+    let mut marc = Vec::new();
+    let mut stdin = std::io::stdin().lock();
+    loop {
+        let mut line = Vec::new();
+        if stdin.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        let line = line.strip_suffix(b"\n").unwrap_or(&line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
         if line.is_empty() {
-            println!("MARC record accepted.");
-            println!("{}", "-".repeat(termsize::get().unwrap().cols as usize));
             break;
         }
-        marc.push_str(&line);
-        marc.push_str("\n");
+        if marc.is_empty() {
+            let path = String::from_utf8_lossy(line);
+            let path = std::path::Path::new(path.trim().trim_matches(['"', '\'']));
+            if path.is_file() {
+                marc = std::fs::read(path)?;
+                break;
+            }
+        }
+        marc.extend_from_slice(line);
+        marc.push(b'\n');
     }
-    marc
+    println!("MARC record accepted.");
+    println!("{}", "-".repeat(termsize::get().unwrap().cols as usize));
+    Ok(marc)
 }
 
-pub fn parse_marc<B>(call_number: CallNumber, buffered: B) -> Result<MarcData, ArchiveError>
+/// Reads a pasted record, a binary (ISO 2709) record, or a saved staff view page, in UTF-8 or MARC-8,
+/// to NFC UTF-8 fields.
+///
+/// A binary record's subfields are kept as they are, so a `$` or `‡` in its data isn't read as a delimiter.
+pub fn read_marc(marc: &[u8]) -> Result<Vec<Record>, ArchiveError> {
+    if is_iso2709(marc) {
+        return parse_iso2709(marc);
+    }
+    // a paste in UTF-8 is kept, and anything else is taken to be MARC-8
+    let text = match std::str::from_utf8(marc) {
+        Ok(text) => text.nfc().collect::<String>(),
+        Err(_) => decode_marc8(marc),
+    };
+    if is_html(&text) {
+        parse_table(staff_view_marc(&text).as_bytes())
+    } else {
+        parse_table(text.as_bytes())
+    }
+}
+
+/// A binary record starts with a 24 character leader whose first 5 characters are the record length.
+fn is_iso2709(marc: &[u8]) -> bool {
+    marc.len() >= 24 &&
+        marc[..5].iter().all(u8::is_ascii_digit) &&
+        marc.contains(&FIELD_TERMINATOR)
+}
+
+const FIELD_TERMINATOR: u8 = 0x1e;
+const RECORD_TERMINATOR: u8 = 0x1d;
+const SUBFIELD_DELIMITER: u8 = 0x1f;
+
+/// Reads the first record of a binary MARC file.
+///
+/// Leader/09 is `a` for UTF-8 and blank for MARC-8. Each field is decoded on its own, since MARC-8
+/// escapes don't carry over from one field to the next.
+fn parse_iso2709(marc: &[u8]) -> Result<Vec<Record>, ArchiveError> {
+    let invalid = |message: &str| ArchiveError::InvalidMarc(message.to_string());
+    let record_end = marc
+        .iter()
+        .position(|b| *b == RECORD_TERMINATOR)
+        .map_or(marc.len(), |end| end + 1);
+    if record_end < 24 {
+        return Err(invalid("the record is shorter than its leader"));
+    }
+    if marc[record_end..].iter().any(|b| !b.is_ascii_whitespace()) {
+        eprintln!("[WARN] The MARC file has more than one record, so only the first is used.");
+    }
+    let marc = &marc[..record_end];
+    let leader = &marc[..24];
+    let is_unicode = leader[9] == b'a';
+    let base_address = std::str::from_utf8(&leader[12..17])
+        .ok()
+        .and_then(|base| base.parse::<usize>().ok())
+        .ok_or_else(|| invalid("the leader has no base address"))?;
+    let directory_end = marc[24..]
+        .iter()
+        .position(|b| *b == FIELD_TERMINATOR)
+        .map(|end| end + 24)
+        .ok_or_else(|| invalid("the directory has no end"))?;
+
+    let mut records = Vec::new();
+    for entry in marc[24..directory_end].chunks(12) {
+        let entry = std::str::from_utf8(entry)
+            .ok()
+            .filter(|entry| entry.is_ascii())
+            .ok_or_else(|| invalid("the directory isn't ASCII"))?;
+        if entry.len() < 12 {
+            return Err(invalid("a directory entry is cut short"));
+        }
+        let tag = entry[..3].to_string();
+        let length = entry[3..7].parse::<usize>().map_err(|_| invalid("a field length isn't a number"))?;
+        let start = entry[7..12].parse::<usize>().map_err(|_| invalid("a field start isn't a number"))?;
+        let field = marc
+            .get(base_address + start..base_address + start + length)
+            .ok_or_else(|| invalid(&format!("the {} field runs past the end of the record", tag)))?;
+        let field = field.strip_suffix(&[FIELD_TERMINATOR]).unwrap_or(field);
+        let field = if is_unicode {
+            String::from_utf8_lossy(field).nfc().collect::<String>()
+        } else {
+            decode_marc8(field)
+        };
+
+        let record = if tag.starts_with("00") {
+            Record {
+                tag,
+                ind: String::new(),
+                subfields: vec![Subfield { code: '_', value: field }],
+            }
+        } else {
+            let mut parts = field.split(SUBFIELD_DELIMITER as char);
+            let ind = parts.next().unwrap_or_default().to_string();
+            let subfields = parts
+                .filter_map(|subfield| {
+                    let mut chars = subfield.chars();
+                    let code = chars.next()?;
+                    Some(Subfield { code, value: chars.as_str().to_string() })
+                })
+                .collect();
+            Record { tag, ind, subfields }
+        };
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Parses the catalog's MARC table, a line per field, e.g. `245\t03\t$aAn Arizona desert-ation.`
+pub fn parse_marc<B>(buffered: B, config: &Config) -> Result<MarcData, ArchiveError>
     where B: BufRead
 {
-    let mut records = Vec::new();
+    MarcData::try_from_records(parse_table(buffered)?, config)
+}
 
+/// Reads the fields of the catalog's MARC table.
+///
+/// Lines that wrapped are joined back onto their field, and fields still parse when a terminal turned the
+/// tabs into spaces or the catalog delimits subfields with `‡` rather than `$`.
+fn parse_table<B>(buffered: B) -> Result<Vec<Record>, ArchiveError>
+    where B: BufRead
+{
+    let mut fields: Vec<String> = Vec::new();
     for line in buffered.lines() {
         let line = line?;
-        let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() == 3 {
-            let subfields_str = parts[2];
-            let mut subfields = Vec::new();
-            let subfields_parts: Vec<&str> = subfields_str.split('$').collect();
-            for subfield in subfields_parts.into_iter().skip(1) {
-                // skip the first empty part
-                let code = subfield.chars().next().unwrap();
-                let value = subfield[1..].to_string();
-                subfields.push(Subfield { code, value });
+        if line.trim().is_empty() {
+            continue;
+        }
+        match fields.last_mut() {
+            Some(field) if !starts_field(&line) && !line.contains('\t') => {
+                // a line that wrapped, e.g. the rest of a long 520
+                field.push(' ');
+                field.push_str(line.trim());
             }
+            _ if starts_field(&line) => fields.push(line.trim_end().to_string()),
+            // the header row, e.g. `Tag\tInd.\tSubfields`, or another table pasted along with it
+            _ => {}
+        }
+    }
 
-            if subfields.is_empty() {
-                // then this is probably a serial record, such as the ocn893691141 or OCoLC.
-                // we can just push the whole thing as a subfield.
-                subfields.push(Subfield {
-                    code: '_',
-                    value: subfields_str.to_string(),
-                });
-            }
+    Ok(
+        fields
+            .iter()
+            .map(|field| parse_field(field))
+            .collect()
+    )
+}
+
+/// A field starts with its tag and a tab, e.g. `245\t03\t$a`. With the tabs lost, the tag has to be
+/// followed by at most 2 indicators and the first subfield, e.g. `245 03 ‡a` or `24503‡a`, so that a
+/// wrapped line like `100 copies were printed.` isn't taken for a field. Control fields have no
+/// subfields, e.g. `008 750101c19679999azu`.
+fn starts_field(line: &str) -> bool {
+    let Some(tag) = line.get(..3).filter(|tag| tag.chars().all(|c| c.is_ascii_digit())) else {
+        return false;
+    };
+    let rest = &line[3..];
+    if rest.starts_with('\t') {
+        return true;
+    }
+    if tag.starts_with("00") {
+        return rest.starts_with(' ');
+    }
+    let Some(start) = rest.find(SUBFIELD_DELIMITERS) else {
+        return false;
+    };
+    let indicators = &rest[..start];
+    indicators.chars().all(|c| c.is_whitespace() || c.is_ascii_digit() || c == '#' || c == '_') &&
+        indicators.chars().filter(|c| !c.is_whitespace()).count() <= 2
+}
+
+/// `$` is how the catalog copies subfields, and `‡` how it displays them in the staff view.
+const SUBFIELD_DELIMITERS: [char; 2] = ['$', '‡'];
+
+/// Reads one field of the MARC table into a record.
+fn parse_field(field: &str) -> Record {
+    let tag = field[..3].to_string();
+    let rest = &field[3..];
+    let (ind, subfields_str) = match rest.strip_prefix('\t').and_then(|rest| rest.split_once('\t')) {
+        // `245\t03\t$a...`, as the catalog copies it
+        Some((ind, subfields_str)) => (ind.to_string(), subfields_str),
+        // `245 03 $a...`, with the tabs lost
+        None if tag.starts_with("00") => (String::new(), rest.trim_start()),
+        None => match rest.find(SUBFIELD_DELIMITERS) {
+            Some(start) => (rest[..start].split_whitespace().collect::<String>(), &rest[start..]),
+            None => (String::new(), rest.trim_start()),
+        },
+    };
 
-            let record = Record {
-                tag: parts[0].to_string(),
-                ind: parts[1].to_string(),
-                subfields,
+    // a line displayed with `‡` may still have a `$` in it, e.g. `$5.00`
+    let delimiter = if subfields_str.contains('‡') { '‡' } else { '$' };
+    let mut subfields = Vec::new();
+    if !tag.starts_with("00") {
+        for subfield in subfields_str.split(delimiter).skip(1) {
+            // skip the first empty part
+            let mut chars = subfield.chars();
+            let Some(code) = chars.next() else {
+                continue;
             };
-            records.push(record);
+            let value = chars.as_str();
+            // the staff view puts a space after the code, e.g. `‡a Phoenix`
+            let value = if delimiter == '‡' { value.trim() } else { value };
+            subfields.push(Subfield { code, value: value.to_string() });
         }
     }
 
-    MarcData::try_from_records(call_number, records)
+    if subfields.is_empty() {
+        // then this is probably a serial record, such as the ocn893691141 or OCoLC.
+        // we can just push the whole thing as a subfield.
+        subfields.push(Subfield {
+            code: '_',
+            value: subfields_str.to_string(),
+        });
+    }
+
+    Record { tag, ind, subfields }
 }
 
 impl MarcData {
-    fn try_from_records(
-        call_number: CallNumber,
-        records: Vec<Record>
-    ) -> Result<Self, ArchiveError> {
+    pub fn try_from_records(records: Vec<Record>, config: &Config) -> Result<Self, ArchiveError> {
+        // marc 852, 099, 090, 086, or 050, in the configured order.
+        // the operator confirms or replaces it when prompted.
+        let call_number = propose_call_number(&records, &config.call_number_fields).unwrap_or(
+            CallNumber::Periodical
+        );
         // marc 100, 110, 700, 710
         let mut creators: Vec<String> = Vec::new();
-        // marc 260 or 264.b
-        let mut publisher: String = String::new();
+        // marc 260, and 264 by its 2nd indicator. the publisher is picked after the loop.
+        let mut publication_statements: Vec<Record> = Vec::new();
         // marc 610, 650, possibly any 600.
         let mut subject_headings: Vec<String> = Vec::new();
-        // marc 001 or 003.
+        // marc 035.a with an (OCoLC) prefix.
         let mut oclc_number: Option<String> = None;
+        // marc 001, when 003 says OCoLC or 001 has an OCLC prefix.
+        let mut control_number: Option<String> = None;
+        let mut control_number_identifier: Option<String> = None;
+        // marc 035.z with an (OCoLC) prefix.
+        let mut cancelled_oclc_numbers: Vec<String> = Vec::new();
+        // marc 022.a and .l, and 010.a.
+        let mut issn: Option<String> = None;
+        let mut linking_issn: Option<String> = None;
+        let mut lccn: Option<String> = None;
+        // problems with the record for the operator to review.
+        let mut review_notes: Vec<String> = Vec::new();
+        // marc 008, the fixed-length data elements.
+        let mut fixed_field: Option<FixedField> = None;
+        // marc 310.a
+        let mut frequency: Option<String> = None;
+        // marc 321.a
+        let mut former_frequencies: Vec<String> = Vec::new();
+        // marc 041.a, ISO 639-2 codes.
+        let mut language_codes: Vec<String> = Vec::new();
+        // marc 600, people the periodical is about.
+        let mut persons: Vec<String> = Vec::new();
+        // marc 611 or 111, meetings and conferences.
+        let mut events: Vec<String> = Vec::new();
+        // marc 610 or 110, corporate bodies.
+        let mut agencies: Vec<String> = Vec::new();
+        // marc 110 and 710 units, jurisdiction first.
+        let mut corporate_names: Vec<Vec<String>> = Vec::new();
+        // marc 086, a government document number. 1st indicator 0 is a federal SuDoc number.
+        let mut has_document_number = false;
+        let mut has_sudoc_number = false;
+        // marc 110 or 710 with 1st indicator 1, a jurisdiction name, e.g. `Arizona. Dept. of Health`.
+        let mut has_jurisdiction_name = false;
+        // marc 245.a, .b, .n, and .p, and 246.a and .b.
+        let mut title: Option<String> = None;
+        let mut title_proper: Option<String> = None;
+        let mut variant_titles: Vec<String> = Vec::new();
+        // marc 520.a and .b, 500.a, and 588.a.
+        let mut summaries: Vec<String> = Vec::new();
+        let mut general_notes: Vec<String> = Vec::new();
+        let mut source_notes: Vec<String> = Vec::new();
+        // marc 245.c, 508.a, and 511.a, the statements of responsibility and credits.
+        let mut credits: Vec<String> = Vec::new();
+        // marc 780 and 785, the titles this one continues and is continued by.
+        let mut preceding_titles: Vec<LinkedTitle> = Vec::new();
+        let mut succeeding_titles: Vec<LinkedTitle> = Vec::new();
+        // marc 651.a, 6XX.z, 043.a, and 260 or 264.a.
+        let mut places: Vec<PlaceSource> = Vec::new();
 
         for record in records {
             match record.tag.as_str() {
-                "700" => {
-                    creators.extend(
+                "111" => {
+                    if let Some(name) = name_heading(&record).filter(|name| !events.contains(name)) {
+                        events.push(name);
+                    }
+                }
+                "100" | "110" | "700" | "710" => {
+                    if record.tag == "110" || record.tag == "710" {
+                        has_jurisdiction_name |= record.indicators().0 == '1';
+                        let units = corporate_units(&record);
+                        if !units.is_empty() {
+                            corporate_names.push(units);
+                        }
+                    }
+                    if record.tag == "110" {
+                        if let Some(name) = name_heading(&record).filter(|name| !agencies.contains(name)) {
+                            agencies.push(name);
+                        }
+                    }
+                    match creator(&record, config.relator_terms) {
+                        // the main entry comes first, even when the catalog lists it after the added entries
+                        Some(name) if record.tag.starts_with('1') => creators.insert(0, name),
+                        Some(name) => creators.push(name),
+                        None => {}
+                    }
+                }
+                "260" | "264" => {
+                    publication_statements.push(record);
+                }
+                "600" | "610" | "611" | "630" | "650" | "651" | "655" => {
+                    if let Some(heading) = subject_heading(&record) {
+                        subject_headings.push(heading);
+                        let names = match record.tag.as_str() {
+                            "600" => Some(&mut persons),
+                            "610" => Some(&mut agencies),
+                            "611" => Some(&mut events),
+                            _ => None,
+                        };
+                        if let Some(names) = names {
+                            if let Some(name) = name_heading(&record).filter(|name| !names.contains(name)) {
+                                names.push(name);
+                            }
+                        }
+                        if record.tag == "651" {
+                            places.extend(
+                                record.subfields
+                                    .iter()
+                                    .find(|sf| sf.code == 'a')
+                                    .map(|sf| PlaceSource::Heading(sf.value.clone()))
+                            );
+                        }
+                        let subdivisions = record.subfields
+                            .iter()
+                            .filter(|sf| sf.code == 'z')
+                            .map(|sf| sf.value.clone())
+                            .collect::<Vec<_>>();
+                        if !subdivisions.is_empty() {
+                            places.push(PlaceSource::Subdivisions(subdivisions));
+                        }
+                    }
+                }
+                "086" => {
+                    has_document_number = true;
+                    has_sudoc_number |= record.indicators().0 == '0';
+                }
+                "245" => {
+                    credits.extend(
                         record.subfields
                             .iter()
-                            .filter(|sf| sf.code == 'a')
-                            .map(|sf| {
-                                // split on comma and reverse the order.
-                                // e.g. "Smith, John A. (Date)" -> "John A. Smith (Date)"
-                                let parts = sf.value.split(", ").collect::<Vec<&str>>();
-                                let string = if parts.len() == 2 {
-                                    format!("{} {}", parts[1], parts[0])
-                                } else {
-                                    sf.value.clone()
-                                };
-                                string.trim_end_matches(is_grammatical_punctuation).to_string()
-                            })
+                            .filter(|sf| sf.code == 'c')
+                            .map(|sf| sf.value.trim().to_string())
                     );
+                    title = build_title(&record);
+                    title_proper = record.subfields
+                        .iter()
+                        .find(|sf| sf.code == 'a')
+                        .map(|sf| trim_isbd_punctuation(&sf.value));
                 }
-                "100" | "110" | "710" => {
-                    creators.extend(
+                "246" => variant_titles.extend(build_title(&record)),
+                "508" | "511" => {
+                    credits.extend(
                         record.subfields
                             .iter()
                             .filter(|sf| sf.code == 'a')
-                            .map(|sf|
-                                sf.value
-                                    .clone()
-                                    .trim_end_matches(is_grammatical_punctuation)
-                                    .to_string()
-                            )
+                            .map(|sf| sf.value.trim().to_string())
                     );
                 }
-                "260" | "264" => {
-                    publisher = record.subfields
+                "520" => {
+                    let summary = record.subfields
                         .iter()
-                        .filter(|sf| sf.code == 'b')
-                        .map(|sf| sf.value.trim_end_matches(is_grammatical_punctuation).to_string())
-                        .collect::<Vec<String>>()
-                        .join(" ")
-                        .trim_end_matches(is_grammatical_punctuation)
-                        .to_string();
-                }
-                "610" | "650" => {
-                    let mut subject = Vec::default();
-                    for Subfield { code, value } in record.subfields {
+                        .filter(|sf| sf.code == 'a' || sf.code == 'b')
+                        .map(|sf| sf.value.trim())
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    if !summary.is_empty() {
+                        summaries.push(summary);
+                    }
+                }
+                "500" => {
+                    general_notes.extend(
+                        record.subfields
+                            .iter()
+                            .filter(|sf| sf.code == 'a')
+                            .map(|sf| sf.value.trim().to_string())
+                    );
+                }
+                "588" => {
+                    source_notes.extend(
+                        record.subfields
+                            .iter()
+                            .filter(|sf| sf.code == 'a')
+                            .map(|sf| sf.value.trim().to_string())
+                    );
+                }
+                "780" => preceding_titles.extend(linked_title(&record)),
+                "785" => succeeding_titles.extend(linked_title(&record)),
+                "022" => {
+                    for Subfield { code, value } in &record.subfields {
+                        let value = value.trim().trim_end_matches([',', ';', '.']);
                         match code {
-                            'a' => subject.push(value),
-                            'x' | 'v' | 'z' => {
-                                subject.push("--".to_string()); // em dash
-                                subject.push(value);
-                            }
-                            'd' => {
-                                subject.push(", ".to_string()); // em dash
-                                subject.push(value);
+                            'a' | 'l' => {
+                                match normalize_issn(value) {
+                                    Some(valid) if *code == 'a' && issn.is_none() => issn = Some(valid),
+                                    Some(valid) if *code == 'l' => linking_issn = Some(valid),
+                                    Some(_) => {}
+                                    None =>
+                                        review_notes.push(
+                                            format!("ISSN \"{}\" in 022 ${} has an invalid check digit or format.", value, code)
+                                        ),
+                                }
                             }
-                            _ => {
-                                println!("[WARN] UNKNOWN SUBFIELD CODE FOR SUBJECTS, IGNORING: {}", code);
+                            'y' => review_notes.push(format!("Incorrect ISSN in 022 $y: {}.", value)),
+                            'z' | 'm' => review_notes.push(format!("Cancelled ISSN in 022 ${}: {}.", code, value)),
+                            _ => {}
+                        }
+                    }
+                }
+                "010" => {
+                    for Subfield { code, value } in &record.subfields {
+                        match code {
+                            'a' if lccn.is_none() => {
+                                lccn = normalize_lccn(value);
+                                if lccn.is_none() {
+                                    review_notes.push(format!("LCCN \"{}\" in 010 is not a valid LCCN.", value.trim()));
+                                }
                             }
+                            'z' => review_notes.push(format!("Cancelled or invalid LCCN in 010 $z: {}.", value.trim())),
+                            _ => {}
                         }
                     }
-                    subject_headings.push(
-                        subject.join("").trim_end_matches(is_grammatical_punctuation).to_string()
+                }
+                "043" => {
+                    places.extend(
+                        record.subfields
+                            .iter()
+                            .filter(|sf| sf.code == 'a')
+                            .map(|sf| PlaceSource::AreaCode(sf.value.clone()))
+                    );
+                }
+                "008" => {
+                    // a collapsed 008 from the catalog's display is rejected, 008/28 can't be located in it
+                    let value = record.subfields.first().map(|sf| sf.value.as_str()).unwrap_or_default();
+                    match FixedField::parse(value) {
+                        Ok(parsed) => fixed_field = Some(parsed),
+                        Err(err) => review_notes.push(format!("The 008 was not used: {}.", err)),
+                    }
+                }
+                "041" => {
+                    // a $2 means the codes come from some other list than ISO 639-2
+                    let is_iso639_2 = record.subfields
+                        .iter()
+                        .all(|sf| sf.code != '2' || sf.value.trim().starts_with("iso639-2"));
+                    if is_iso639_2 {
+                        language_codes.extend(
+                            record.subfields
+                                .iter()
+                                .filter(|sf| sf.code == 'a')
+                                .flat_map(|sf| split_language_codes(&sf.value))
+                        );
+                    }
+                }
+                "310" => {
+                    frequency = record.subfields
+                        .iter()
+                        .find(|sf| sf.code == 'a')
+                        .map(|sf| sf.value.trim_end_matches(is_grammatical_punctuation).to_string());
+                }
+                "321" => {
+                    former_frequencies.extend(
+                        record.subfields
+                            .iter()
+                            .filter(|sf| sf.code == 'a')
+                            .map(|sf| sf.value.trim_end_matches(is_grammatical_punctuation).to_string())
                     );
                 }
-                "001" | "003" => {
-                    if
-                        oclc_number.is_none() &&
-                        record.subfields.first().is_some_and(|sf| sf.code == '_')
-                    {
-                        let value = record.subfields.first().unwrap().value.clone();
-                        // rip off any leading non-numeric characters
-                        let value = value.trim_start_matches(|c: char| !c.is_numeric());
-                        // pad the left side with zeros so that it's 9 characters long
-                        let value = format!("{:0>9}", value);
-                        oclc_number = Some(value);
+                "001" => {
+                    control_number = record.subfields.first().map(|sf| sf.value.trim().to_string());
+                }
+                "003" => {
+                    control_number_identifier = record.subfields.first().map(|sf| sf.value.trim().to_string());
+                }
+                "035" => {
+                    for Subfield { code, value } in &record.subfields {
+                        let Some(number) = value.trim().strip_prefix("(OCoLC)") else {
+                            continue;
+                        };
+                        match code {
+                            'a' if oclc_number.is_none() => {
+                                oclc_number = normalize_oclc_number(number);
+                            }
+                            'z' => cancelled_oclc_numbers.extend(normalize_oclc_number(number)),
+                            _ => {}
+                        }
                     }
                 }
                 _ => {}
             }
         }
 
+        // 001 is only an OCLC number when 003 says so, or when it carries an OCLC prefix,
+        // otherwise it's a local system key (e.g. Sirsi's).
+        let control_oclc_number = control_number.as_deref().and_then(|number| {
+            let has_prefix = ["ocm", "ocn", "on"].iter().any(|p| number.starts_with(p));
+            let is_oclc = control_number_identifier.as_deref() == Some("OCoLC");
+            if has_prefix || is_oclc {
+                normalize_oclc_number(number)
+            } else {
+                None
+            }
+        });
+        if let (Some(oclc), Some(control)) = (&oclc_number, &control_oclc_number) {
+            if oclc != control {
+                review_notes.push(
+                    format!("The OCLC number in 001 ({}) disagrees with 035 ({}), using 035.", control, oclc)
+                );
+            }
+        }
+        let oclc_number = oclc_number.or(control_oclc_number);
+        if oclc_number.is_none() {
+            review_notes.push("No OCLC number was found in 035 or 001.".to_string());
+        }
+        if !cancelled_oclc_numbers.is_empty() {
+            review_notes.push(
+                format!("Cancelled OCLC numbers in 035 $z: {}.", cancelled_oclc_numbers.join(", "))
+            );
+        }
+        // 008/35-37 comes first, it's the predominant language.
+        let fixed_field_language = fixed_field.as_ref().map(|f| f.language.clone());
+        let mut languages: Vec<String> = Vec::new();
+        for code in fixed_field_language.into_iter().chain(language_codes) {
+            if !is_language_code(&code) {
+                continue;
+            }
+            match language_name(&code) {
+                Some(name) if !languages.iter().any(|l| l == name) => languages.push(name.to_string()),
+                Some(_) => {}
+                None => review_notes.push(format!("Unknown language code \"{}\" in the record.", code)),
+            }
+        }
+
+        let government_level = fixed_field
+            .as_ref()
+            .and_then(|f| f.government_level())
+            .or(if has_sudoc_number { Some(GovernmentLevel::Federal) } else { None });
+        let is_government_publication = government_level.is_some() || has_document_number || has_jurisdiction_name;
+        let government_agencies = if is_government_publication {
+            let (agencies, notes) = GovernmentAgencies::from_names(&corporate_names, government_level, &config.agencies);
+            review_notes.extend(notes);
+            if agencies.is_empty() {
+                review_notes.push(
+                    "This looks like a government publication, but no issuing agency was found.".to_string()
+                );
+            }
+            agencies
+        } else {
+            GovernmentAgencies::default()
+        };
+
+        let (creators, creator_notes) = dedupe_creators(creators);
+        review_notes.extend(creator_notes);
+
+        let publishers = publishers(&publication_statements, &mut review_notes);
+        let publisher = publishers
+            .iter()
+            .map(|p| p.name.clone())
+            .fold(Vec::<String>::new(), |mut names, name| {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+                names
+            })
+            .join("|");
+        // the place of publication only comes from the statements the publisher came from
+        places.extend(
+            publication_records(&publication_statements)
+                .into_iter()
+                .flat_map(|record| record.subfields.iter().filter(|sf| sf.code == 'a'))
+                .map(|sf| PlaceSource::Publication(sf.value.clone()))
+        );
+
+        // notes about how the record was cataloged don't describe the periodical
+        let general_notes = general_notes
+            .into_iter()
+            .filter(|note| {
+                !CATALOGING_NOTES.iter().any(|prefix| note.starts_with(prefix)) && !source_notes.contains(note)
+            })
+            .collect();
+        let place_of_publication = publication_records(&publication_statements)
+            .into_iter()
+            .flat_map(|record| record.subfields.iter().filter(|sf| sf.code == 'a'))
+            // the period is kept, it usually ends an abbreviation, e.g. `Ariz.`
+            .map(|sf| sf.value.trim().trim_start_matches('[').trim_end_matches([']', ' ', ':', ';', ',']).to_string())
+            .find(|place| !place.is_empty());
+
+        let (geography, geography_notes) = Geography::from_sources(&places);
+        review_notes.extend(geography_notes);
+
+        for note in &review_notes {
+            eprintln!("[WARN] {}", note);
+        }
+
         Ok(MarcData {
             call_number,
             creators,
             publisher,
+            publishers,
             subject_headings,
-            oclc_number: oclc_number.expect("No OCLC number found."),
+            oclc_number,
+            issn,
+            linking_issn,
+            lccn,
+            fixed_field,
+            frequency,
+            former_frequencies,
+            languages,
+            summaries,
+            general_notes,
+            credits,
+            place_of_publication,
+            title,
+            title_proper,
+            variant_titles,
+            preceding_titles,
+            succeeding_titles,
+            persons,
+            events,
+            agencies,
+            government_agencies,
+            geography,
+            review_notes,
         })
     }
 }
 
-fn is_grammatical_punctuation(c: char) -> bool {
-    match c {
-        '.' | ',' | ' ' | ';' => true,
+/// Builds a title from `$a`, `$b`, `$n`, and `$p` without the ISBD punctuation between them.
+///
+/// E.g. `$aBulletin.$nNo. 3,$pWater resources :$ba report /$cby...` -> `Bulletin. No. 3, Water resources: a report`.
+fn build_title(record: &Record) -> Option<String> {
+    let mut title = String::new();
+    let mut previous = None;
+    for Subfield { code, value } in &record.subfields {
+        if !matches!(code, 'a' | 'b' | 'n' | 'p') {
+            continue;
+        }
+        let value = trim_isbd_punctuation(value);
+        if value.is_empty() {
+            continue;
+        }
+        let separator = match (previous, code) {
+            (None, _) => "",
+            (_, 'b') => ": ",
+            (Some('n'), 'p') => ", ",
+            _ => ". ",
+        };
+        title.push_str(separator);
+        title.push_str(&value);
+        previous = Some(*code);
+    }
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+/// Trims the ISBD punctuation that ends a title element, e.g. ` :`, ` /`, ` =`, ` ;`, and a period.
+fn trim_isbd_punctuation(value: &str) -> String {
+    let trimmed = value.trim().trim_end_matches([' ', ':', '/', '=', ';', ',']);
+    trim_terminal_punctuation(trimmed)
+}
+
+/// Reads a 780 or 785 linking entry, e.g. `$tArizona desert-ation$w(OCoLC)12345` with 2nd indicator 0.
+fn linked_title(record: &Record) -> Option<LinkedTitle> {
+    let (_, relationship) = record.indicators();
+    let relationship = match (record.tag.as_str(), relationship) {
+        ("780", '0') => "Continues",
+        ("780", '1') => "Continues in part",
+        ("780", '2') => "Supersedes",
+        ("780", '3') => "Supersedes in part",
+        ("780", '4') => "Formed by the union of",
+        ("780", '5') => "Absorbed",
+        ("780", '6') => "Absorbed in part",
+        ("780", '7') => "Separated from",
+        ("785", '0') => "Continued by",
+        ("785", '1') => "Continued in part by",
+        ("785", '2') => "Superseded by",
+        ("785", '3') => "Superseded in part by",
+        ("785", '4') => "Absorbed by",
+        ("785", '5') => "Absorbed in part by",
+        ("785", '6') => "Split into",
+        ("785", '7') => "Merged to form",
+        ("785", '8') => "Changed back to",
+        ("780", _) => "Preceded by",
+        _ => "Succeeded by",
+    };
+    // the title, or the main entry when the linked record has no separate title
+    let title = record.subfields
+        .iter()
+        .find(|sf| sf.code == 't')
+        .or_else(|| record.subfields.iter().find(|sf| sf.code == 'a'))
+        .map(|sf| trim_terminal_punctuation(&sf.value))
+        .filter(|title| !title.is_empty())?;
+    let oclc_number = record.subfields
+        .iter()
+        .filter(|sf| sf.code == 'w')
+        .find_map(|sf| sf.value.trim().strip_prefix("(OCoLC)").and_then(normalize_oclc_number));
+    let issn = record.subfields
+        .iter()
+        .find(|sf| sf.code == 'x')
+        .and_then(|sf| normalize_issn(&sf.value));
+    Some(LinkedTitle {
+        relationship: relationship.to_string(),
+        title,
+        oclc_number,
+        issn,
+    })
+}
+
+/// The 260s and 264s that name the publisher, rather than a distributor, manufacturer, or copyright date.
+///
+/// 264 with 2nd indicator 1 is preferred, 260 is only used without one.
+fn publication_records(statements: &[Record]) -> Vec<&Record> {
+    let is_publication = |record: &Record| record.tag == "264" && record.indicators().1 == '1';
+    if statements.iter().any(is_publication) {
+        statements.iter().filter(|record| is_publication(record)).collect()
+    } else {
+        statements.iter().filter(|record| record.tag == "260").collect()
+    }
+}
+
+/// 500 notes that describe the record rather than the periodical, like those in 588.
+const CATALOGING_NOTES: [&str; 5] = [
+    "Description based on",
+    "Latest issue consulted",
+    "Title from",
+    "Caption title",
+    "Cover title",
+];
+
+/// Words that mark a 260 or 264 `$b` as a printer rather than a publisher.
+const PRINTER_WORDS: [&str; 5] = ["printer", "printers", "printing", "printery", "lithographers"];
+
+/// The publishers of the periodical, earliest first.
+///
+/// The 1st indicator orders the statements: blank for the earliest, 2 for intervening, and 3 for the
+/// current publisher. Printers are skipped and noted for review.
+fn publishers(statements: &[Record], review_notes: &mut Vec<String>) -> Vec<PublisherStatement> {
+    let mut statements = publication_records(statements);
+    statements.sort_by_key(|record| match record.indicators().0 {
+        '2' => 1,
+        '3' => 2,
+        _ => 0,
+    });
+
+    let mut publishers = Vec::new();
+    for record in statements {
+        let (printers, names): (Vec<String>, Vec<String>) = record.subfields
+            .iter()
+            .filter(|sf| sf.code == 'b')
+            .map(|sf| trim_terminal_punctuation(&sf.value))
+            .filter(|name| !name.is_empty())
+            .partition(|name| {
+                name.to_lowercase()
+                    .split(|c: char| !c.is_alphabetic())
+                    .any(|word| PRINTER_WORDS.contains(&word))
+            });
+        for printer in printers {
+            review_notes.push(format!("\"{}\" in {} looks like a printer, it was not used as the publisher.", printer, record.tag));
+        }
+        if names.is_empty() {
+            continue;
+        }
+        // `$c1967-1975.`, `$c[1967]-`
+        let years = record.subfields
+            .iter()
+            .filter(|sf| sf.code == 'c')
+            .flat_map(|sf| years(&sf.value))
+            .collect::<Vec<_>>();
+        publishers.push(PublisherStatement {
+            name: names.join("|"),
+            start_year: years.first().copied(),
+            end_year: years.get(1).copied(),
+        });
+    }
+    publishers
+}
+
+/// The four digit years in a date statement, e.g. `[1967]-1975.` -> `[1967, 1975]`.
+fn years(value: &str) -> Vec<u16> {
+    value
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|digits| digits.len() == 4)
+        .filter_map(|digits| digits.parse().ok())
+        .collect()
+}
+
+/// Builds a 6XX subject heading with Library of Congress punctuation.
+///
+/// E.g. `$aSmith, John,$d1900-1980$xHomes and haunts$zArizona.` -> `Smith, John, 1900-1980--Homes and haunts--Arizona`.
+///
+/// Returns `None` for headings from other thesauri (2nd indicator other than 0, or 7 with a non-LC `$2`).
+fn subject_heading(record: &Record) -> Option<String> {
+    if !is_lc_heading(record) {
+        return None;
+    }
+    build_heading(&record.tag, &record.subfields, &format!("{} subject heading", record.tag))
+}
+
+/// Whether a 6XX is a Library of Congress heading (2nd indicator 0, or 7 with an LC `$2`).
+fn is_lc_heading(record: &Record) -> bool {
+    let (_, thesaurus) = record.indicators();
+    let source = record.subfields
+        .iter()
+        .find(|sf| sf.code == '2')
+        .map(|sf| sf.value.trim().trim_end_matches('.').to_lowercase());
+    match thesaurus {
+        '0' => true,
+        '7' => source.as_deref().is_some_and(|source| LC_THESAURI.contains(&source)),
         _ => false,
     }
 }
+
+/// The name at the start of a 1XX or 6XX, without its title or subdivisions.
+///
+/// Personal names are inverted, e.g. `$aSmith, John,$d1900-1980$xHomes and haunts` -> `John Smith, 1900-1980`.
+/// Meetings keep their number, date, and place, e.g. `Arizona Water Conference (1st : 1967 : Phoenix, Ariz.)`.
+fn name_heading(record: &Record) -> Option<String> {
+    // 1XX names are punctuated like their 6XX subjects
+    let tag = format!("6{}", record.tag.get(1..).unwrap_or_default());
+    // only surnames (1st indicator 1) are inverted, not forenames (0) or family names (3), e.g. `Smith family`.
+    // the catalog can drop a blank indicator, so a name without one is inverted when it has a comma.
+    let is_inverted = tag == "600" && matches!(record.indicators().0, '1' | ' ');
+    let subfields = record.subfields
+        .iter()
+        .take_while(|sf| !matches!(sf.code, 't' | 'v' | 'x' | 'y' | 'z'))
+        .map(|sf| Subfield {
+            code: sf.code,
+            value: if sf.code == 'a' && is_inverted { invert_name(&sf.value) } else { sf.value.clone() },
+        })
+        .collect::<Vec<_>>();
+    build_heading(&tag, &subfields, &format!("{} name", record.tag))
+}
+
+/// Builds a creator from a 100, 110, 700, or 710, e.g. `$aSmith, John A.,$d1900-1980,$eeditor.` -> `John A. Smith, 1900-1980`.
+///
+/// Corporate names keep their subordinate units, e.g. `$aArizona.$bDept. of Health.` -> `Arizona. Dept. of Health`.
+/// Relator terms (`$e`) and codes (`$4`) are only kept when `relator_terms` is set, e.g. `John A. Smith, 1900-1980, editor`.
+fn creator(record: &Record, relator_terms: bool) -> Option<String> {
+    let name = name_heading(record)?;
+    if !relator_terms {
+        return Some(name);
+    }
+    let relators = record.subfields
+        .iter()
+        .filter(|sf| sf.code == 'e' || sf.code == '4')
+        .map(|sf| trim_terminal_punctuation(&sf.value))
+        .filter(|relator| !relator.is_empty())
+        .collect::<Vec<_>>();
+    if relators.is_empty() {
+        Some(name)
+    } else {
+        Some(format!("{}, {}", name, relators.join(", ")))
+    }
+}
+
+/// Normalizes creators to NFC and drops repeats, keeping the first spelling, e.g. the main entry's.
+///
+/// Creators are the same when they only differ by case, punctuation, or Unicode form, e.g. a 110 and 710 of
+/// `Desert Sunshine Exposure Tests (Phoenix, Ariz.)`. Returns a note for each merge.
+fn dedupe_creators(creators: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let mut kept: Vec<(String, String)> = Vec::new();
+    let mut notes = Vec::new();
+    for creator in creators {
+        let creator = creator.nfc().collect::<String>();
+        let key = creator
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        match kept.iter().find(|(k, _)| *k == key) {
+            Some((_, first)) if *first == creator =>
+                notes.push(format!("Creator \"{}\" was listed more than once, it was merged.", creator)),
+            Some((_, first)) =>
+                notes.push(format!("Creator \"{}\" was merged into \"{}\".", creator, first)),
+            None => kept.push((key, creator)),
+        }
+    }
+    (kept.into_iter().map(|(_, creator)| creator).collect(), notes)
+}
+
+/// Inverts a personal name, e.g. `Smith, John A.,` -> `John A. Smith`.
+///
+/// Names without exactly one comma, e.g. `Caryl` or `John Paul`, are kept as they are.
+fn invert_name(name: &str) -> String {
+    let name = name.trim().trim_end_matches([',', ' ']);
+    let parts = name.split(", ").collect::<Vec<&str>>();
+    if parts.len() == 2 {
+        format!("{} {}", parts[1], parts[0])
+    } else {
+        name.to_string()
+    }
+}
+
+/// The jurisdiction or body and its subordinate units of a 110 or 710, e.g. `["Arizona", "Dept. of Health"]`.
+fn corporate_units(record: &Record) -> Vec<String> {
+    record.subfields
+        .iter()
+        .take_while(|sf| !matches!(sf.code, 't' | 'v' | 'x' | 'y' | 'z'))
+        .filter(|sf| sf.code == 'a' || sf.code == 'b')
+        .map(|sf| trim_terminal_punctuation(&sf.value))
+        .filter(|unit| !unit.is_empty())
+        .collect()
+}
+
+/// Joins the subfields of a 6XX with Library of Congress punctuation.
+///
+/// `field` names the field in warnings, e.g. `650 subject heading` or `700 name`.
+fn build_heading(tag: &str, subfields: &[Subfield], field: &str) -> Option<String> {
+    enum Separator {
+        Subdivision,
+        Comma,
+        Space,
+        Period,
+    }
+
+    let mut heading = String::new();
+    for Subfield { code, value } in subfields {
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        let separator = match (tag, *code) {
+            (_, 'a') if heading.is_empty() => None,
+            // form, general, chronological, and geographic subdivisions
+            (_, 'v' | 'x' | 'y' | 'z') => Some(Separator::Subdivision),
+            // dates and titles of a person, e.g. `Smith, John, Sir, 1900-1980`
+            ("600", 'c' | 'd') => Some(Separator::Comma),
+            // numeration and fuller form of name, e.g. `John Paul II` or `Smith, J. R. (John Robert)`
+            ("600", 'b' | 'q') => Some(Separator::Space),
+            // meeting number, date, and place, e.g. `Conference (1st : 1967 : Phoenix, Ariz.)`
+            ("610" | "611", 'n' | 'd' | 'c') => Some(Separator::Space),
+            // the date a treaty was signed, e.g. `Treaty of Guadalupe Hidalgo (1848)`
+            ("630", 'd') => Some(Separator::Space),
+            // subordinate units, titles, and parts of titles
+            ("611", 'e') | (_, 'b' | 't' | 'p' | 'n' | 'l' | 'f' | 'k' | 's' | 'm' | 'o' | 'r' | 'g') =>
+                Some(Separator::Period),
+            // relator terms, relationship information, thesaurus, authority links, control numbers, and linkage
+            (_, 'e' | 'i' | 'j' | 'w' | '2' | '0' | '1' | '3' | '4' | '6' | '8') => {
+                continue;
+            }
+            _ => {
+                eprintln!("[WARN] Unknown subfield ${} in {}, ignoring it.", code, field);
+                continue;
+            }
+        };
+
+        match separator {
+            None => {}
+            Some(Separator::Subdivision) => {
+                heading = heading.trim_end_matches(is_grammatical_punctuation).to_string();
+                heading.push_str("--");
+            }
+            Some(Separator::Comma) => {
+                heading = heading.trim_end_matches([' ', ',']).to_string();
+                heading.push_str(", ");
+            }
+            Some(Separator::Space) => heading.push(' '),
+            Some(Separator::Period) => {
+                heading = heading.trim_end_matches([' ', ',', ';', ':']).to_string();
+                if !heading.ends_with(['.', '?', '!']) {
+                    heading.push('.');
+                }
+                heading.push(' ');
+            }
+        }
+        heading.push_str(value);
+    }
+
+    let heading = trim_terminal_punctuation(&heading);
+    if heading.is_empty() {
+        None
+    } else {
+        Some(heading)
+    }
+}
+
+/// Trims the punctuation that ends a MARC field, but keeps the period after an initial, e.g. `Caryl, C. R.`.
+fn trim_terminal_punctuation(value: &str) -> String {
+    let trimmed = value.trim_end_matches([' ', ',', ';', ':', '/', '=']);
+    if let Some(without_period) = trimmed.strip_suffix('.') {
+        let last_word = without_period.rsplit([' ', '.']).next().unwrap_or_default();
+        let is_initial = last_word.len() == 1 && last_word.chars().all(|c| c.is_uppercase());
+        if !is_initial {
+            return without_period.trim_end_matches(is_grammatical_punctuation).to_string();
+        }
+    }
+    trimmed.to_string()
+}
+
+/// Strips the `ocm`, `ocn`, or `on` prefix and any leading zeros from an OCLC number.
+///
+/// Numbers under 9 digits are left padded with zeros to 9 digits, longer numbers are kept whole.
+///
+/// E.g. `ocm00012345 -> 000012345`, `on1234567890 -> 1234567890`.
+fn normalize_oclc_number(value: &str) -> Option<String> {
+    let value = value.trim();
+    let value = ["ocm", "ocn", "on"]
+        .iter()
+        .find_map(|prefix| value.strip_prefix(prefix))
+        .unwrap_or(value)
+        .trim();
+    let digits = value
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>();
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        None
+    } else {
+        Some(format!("{:0>9}", digits))
+    }
+}
+
+/// Finds the first call number in the records, checking the tags in priority order.
+///
+/// Local holdings (852) that say PERIODICAL are shelved without a call number.
+fn propose_call_number(records: &[Record], priority: &[String]) -> Option<CallNumber> {
+    let values = |record: &Record, codes: &[char]| -> Vec<String> {
+        codes
+            .iter()
+            .flat_map(|code| {
+                record.subfields
+                    .iter()
+                    .filter(move |sf| sf.code == *code)
+                    .map(|sf| sf.value.trim().to_string())
+            })
+            .filter(|value| !value.is_empty())
+            .collect()
+    };
+
+    for tag in priority {
+        for record in records.iter().filter(|r| &r.tag == tag) {
+            let parts = match tag.as_str() {
+                // prefix, classification, item, suffix
+                "852" => {
+                    let parts = values(record, &['k', 'h', 'i', 'm']);
+                    let says_periodical = parts
+                        .iter()
+                        .chain(values(record, &['j']).iter())
+                        .any(|part| part.to_uppercase().contains("PERIODICAL"));
+                    if says_periodical {
+                        return Some(CallNumber::Periodical);
+                    }
+                    if parts.is_empty() {
+                        values(record, &['j'])
+                    } else {
+                        parts
+                    }
+                }
+                // classification and item number
+                "050" | "090" => values(record, &['a', 'b']),
+                // 099 repeats $a for each part of a local call number, 086 has just the one
+                _ => values(record, &['a']),
+            };
+            if !parts.is_empty() {
+                return Some(CallNumber::Shelf(parts.join(" ")));
+            }
+        }
+    }
+    None
+}
+
+fn is_grammatical_punctuation(c: char) -> bool {
+    matches!(c, '.' | ',' | ' ' | ';')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(lines: &[&str]) -> MarcData {
+        parse_marc(lines.join("\n").as_bytes(), &Config::default()).unwrap()
+    }
+
+    /// The call number proposed for fields, `PERIODICAL` when they say so.
+    fn call_number(fields: &[&str], priority: &[&str]) -> Option<String> {
+        let records = fields.iter().map(|field| parse_field(field)).collect::<Vec<_>>();
+        let priority = priority.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        propose_call_number(&records, &priority).map(|call_number| match call_number {
+            CallNumber::Periodical => "PERIODICAL".to_string(),
+            CallNumber::Shelf(shelf) => shelf,
+        })
+    }
+
+    #[test]
+    fn proposes_call_numbers_in_priority_order() {
+        let fields = [
+            "050\t00\t$aHE5633.A6$bA75",
+            "086\t0 \t$aAZ 1.2:D 45",
+            "090\t  \t$aF806$b.A75",
+        ];
+        let default = ["852", "099", "090", "086", "050"];
+        assert_eq!(call_number(&fields, &default), Some("F806 .A75".to_string()));
+        assert_eq!(call_number(&fields, &["086", "050"]), Some("AZ 1.2:D 45".to_string()));
+        assert_eq!(call_number(&fields, &["050", "090"]), Some("HE5633.A6 A75".to_string()));
+        assert_eq!(call_number(&fields, &["099"]), None);
+    }
+
+    #[test]
+    fn joins_call_number_parts_without_extra_whitespace() {
+        // 099 repeats $a for each part of a local call number
+        assert_eq!(
+            call_number(&["099\t  \t$a 979.1 $a  A75  $a"], &["099"]),
+            Some("979.1 A75".to_string())
+        );
+        assert_eq!(
+            call_number(&["852\t01\t$bMAIN$kRef.$hF806$i.A75 $m1967"], &["852"]),
+            Some("Ref. F806 .A75 1967".to_string())
+        );
+    }
+
+    #[test]
+    fn holdings_that_say_periodical_have_no_call_number() {
+        let fields = ["852\t  \t$bMAIN$jPeriodical", "090\t  \t$aF806$b.A75"];
+        assert_eq!(call_number(&fields, &["852", "090"]), Some("PERIODICAL".to_string()));
+        assert_eq!(call_number(&["852\t  \t$j Stacks 12 "], &["852"]), Some("Stacks 12".to_string()));
+    }
+
+    #[test]
+    fn normalizes_oclc_numbers() {
+        assert_eq!(normalize_oclc_number("ocm00012345"), Some("000012345".to_string()));
+        assert_eq!(normalize_oclc_number("ocn893691141"), Some("893691141".to_string()));
+        assert_eq!(normalize_oclc_number("on1234567890"), Some("1234567890".to_string()));
+        assert_eq!(normalize_oclc_number(" 4 "), Some("000000004".to_string()));
+        // numbers of 9 digits or more are kept whole
+        assert_eq!(normalize_oclc_number("12345678901"), Some("12345678901".to_string()));
+        assert_eq!(normalize_oclc_number("ocm"), None);
+        assert_eq!(normalize_oclc_number("000"), None);
+    }
+
+    #[test]
+    fn reads_the_oclc_number_from_035_and_skips_cancelled_numbers() {
+        let marc = parse(&[
+            "001\t\t4567",
+            "035\t  \t$z(OCoLC)ocm00000001$a(OCoLC)ocm00012345",
+            "035\t  \t$a(Sirsi) a123456",
+        ]);
+        assert_eq!(marc.oclc_number, Some("000012345".to_string()));
+        assert!(marc.review_notes.contains(&"Cancelled OCLC numbers in 035 $z: 000000001.".to_string()));
+        // 001 isn't an OCLC number without an OCLC prefix or 003
+        assert!(!marc.review_notes.iter().any(|note| note.contains("disagrees")));
+    }
+
+    #[test]
+    fn notes_when_001_and_035_disagree() {
+        let marc = parse(&["001\t\t893691141", "003\t\tOCoLC", "035\t  \t$a(OCoLC)12345"]);
+        assert_eq!(marc.oclc_number, Some("000012345".to_string()));
+        assert!(marc.review_notes.contains(
+            &"The OCLC number in 001 (893691141) disagrees with 035 (000012345), using 035.".to_string()
+        ));
+
+        let marc = parse(&["001\t\tocn893691141"]);
+        assert_eq!(marc.oclc_number, Some("893691141".to_string()));
+    }
+
+    #[test]
+    fn records_without_an_oclc_number_have_none() {
+        let marc = parse(&["001\t\ta123456", "035\t  \t$z(OCoLC)ocm00000001"]);
+        assert_eq!(marc.oclc_number, None);
+        assert!(marc.review_notes.contains(&"No OCLC number was found in 035 or 001.".to_string()));
+    }
+
+    /// An 008 for a continuing resource, with its government publication code (28) and language (35-37).
+    fn fixed_field(government: char, language: &str) -> String {
+        format!("750101c19679999azumr p      {}0   a0{} d", government, language)
+    }
+
+    #[test]
+    fn reads_languages_from_008_then_041() {
+        let marc = parse(&[
+            &format!("008\t\t{}", fixed_field(' ', "spa")),
+            "041\t0 \t$aengspa$anav",
+        ]);
+        assert_eq!(marc.languages, ["Spanish", "English", "Navajo"]);
+    }
+
+    #[test]
+    fn skips_codes_that_arent_languages() {
+        let marc = parse(&[
+            &format!("008\t\t{}", fixed_field(' ', "mul")),
+            "041\t0 \t$aundzxx$aeng$axyz",
+            // codes from another list than ISO 639-2
+            "041\t07\t$aen$2iso639-1",
+        ]);
+        assert_eq!(marc.languages, ["English"]);
+        assert!(marc.review_notes.contains(&"Unknown language code \"xyz\" in the record.".to_string()));
+    }
+
+    fn heading(field: &str) -> Option<String> {
+        subject_heading(&parse_field(field))
+    }
+
+    #[test]
+    fn punctuates_subject_headings() {
+        let cases = [
+            ("600\t10\t$aSmith, John,$d1900-1980$xHomes and haunts$zArizona.", "Smith, John, 1900-1980--Homes and haunts--Arizona"),
+            ("600\t00\t$aJohn Paul$bII,$cPope,$d1920-2005.", "John Paul II, Pope, 1920-2005"),
+            ("610\t20\t$aArizona.$bDept. of Health Services$vPeriodicals.", "Arizona. Dept. of Health Services--Periodicals"),
+            (
+                "611\t20\t$aArizona Water Conference$n(1st :$d1967 :$cPhoenix, Ariz.)$vPeriodicals.",
+                "Arizona Water Conference (1st : 1967 : Phoenix, Ariz.)--Periodicals",
+            ),
+            ("630\t00\t$iParodied as:$aTreaty of Guadalupe Hidalgo$d(1848)$w(DLC)n79045353", "Treaty of Guadalupe Hidalgo (1848)"),
+            ("648\t 0\t$aNineteen sixties.", "Nineteen sixties"),
+            ("650\t 0\t$aDeserts$zArizona$vPeriodicals.", "Deserts--Arizona--Periodicals"),
+            ("650\t 0\t$aIndians of North America$xHistory$y20th century.", "Indians of North America--History--20th century"),
+            ("655\t 7\t$aPeriodicals.$2lcgft", "Periodicals"),
+        ];
+        for (field, expected) in cases {
+            assert_eq!(heading(field).as_deref(), Some(expected), "{}", field);
+        }
+    }
+
+    #[test]
+    fn keeps_only_library_of_congress_headings() {
+        assert!(heading("650\t 0\t$aDeserts.").is_some());
+        assert!(heading("650\t 7\t$aDeserts.$2lcsh.").is_some());
+        assert!(heading("650\t 7\t$aDeserts.$2LCSH").is_some());
+        // children's headings, MeSH, local headings, and other thesauri
+        assert!(heading("650\t 1\t$aDeserts.").is_none());
+        assert!(heading("650\t 2\t$aDeserts.").is_none());
+        assert!(heading("650\t 4\t$aDeserts.").is_none());
+        assert!(heading("650\t 7\t$aDeserts.$2fast$0(OCoLC)fst00891142").is_none());
+        assert!(heading("650\t 7\t$aDeserts.").is_none());
+    }
+
+    #[test]
+    fn reads_places_from_651_and_043() {
+        let marc = parse(&[
+            "043\t  \t$an-us-az$an-us-nb",
+            "260\t  \t$aLincoln, Neb. :$bUniversity of Nebraska,$c1967-",
+            "651\t 0\t$aPhoenix (Ariz.)$vPeriodicals.",
+            "650\t 0\t$aDeserts$zArizona$zTucson.",
+        ]);
+        let geography = &marc.geography;
+        assert_eq!(geography.places, ["Phoenix (Ariz.)"]);
+        assert_eq!(geography.cities, ["Phoenix", "Tucson"]);
+        assert_eq!(geography.counties, ["Maricopa County", "Pima County"]);
+        assert_eq!(geography.states, ["Arizona", "Nebraska"]);
+        // the place of publication is only used without subject geography
+        assert!(!geography.cities.contains(&"Lincoln".to_string()));
+    }
+
+    #[test]
+    fn sorts_name_subjects_into_persons_events_and_agencies() {
+        let marc = parse(&[
+            "110\t2 \t$aArizona Historical Society.",
+            "111\t2 \t$aArizona Water Conference$n(1st :$d1967 :$cPhoenix, Ariz.)",
+            "600\t10\t$aSmith, John,$d1900-1980$xHomes and haunts.",
+            "600\t10\t$aSmith, John,$d1900-1980$vCorrespondence.",
+            "610\t20\t$aArizona Historical Society$vPeriodicals.",
+            "610\t20\t$aSalt River Project.$bPublic Affairs Dept.",
+            "611\t20\t$aArizona Water Conference$n(1st :$d1967 :$cPhoenix, Ariz.)$vPeriodicals.",
+            // not Library of Congress headings
+            "600\t14\t$aJones, Mary.",
+            "610\t27\t$aSierra Club.$2fast",
+        ]);
+        assert_eq!(marc.persons, ["John Smith, 1900-1980"]);
+        assert_eq!(marc.events, ["Arizona Water Conference (1st : 1967 : Phoenix, Ariz.)"]);
+        assert_eq!(
+            marc.agencies,
+            ["Arizona Historical Society", "Salt River Project. Public Affairs Dept"]
+        );
+    }
+
+    #[test]
+    fn unknown_government_publications_are_not_government_publications() {
+        let no_agency = "This looks like a government publication, but no issuing agency was found.".to_string();
+        let marc = parse(&[&format!("008\t\t{}", fixed_field('u', "eng"))]);
+        assert!(!marc.review_notes.contains(&no_agency));
+        let marc = parse(&[&format!("008\t\t{}", fixed_field('s', "eng"))]);
+        assert!(marc.review_notes.contains(&no_agency));
+    }
+
+    #[test]
+    fn builds_titles_without_isbd_punctuation() {
+        let title = |field: &str| build_title(&parse_field(field));
+        assert_eq!(
+            title("245\t10\t$aBulletin.$nNo. 3,$pWater resources :$ba report /$cby the Arizona State Land Dept."),
+            Some("Bulletin. No. 3, Water resources: a report".to_string())
+        );
+        assert_eq!(title("245\t03\t$aAn Arizona desert-ation."), Some("An Arizona desert-ation".to_string()));
+        assert_eq!(title("245\t00\t$aArizona highways =$bCarreteras de Arizona."), Some("Arizona highways: Carreteras de Arizona".to_string()));
+        assert_eq!(title("246\t13\t$aArizona desert ation ;"), Some("Arizona desert ation".to_string()));
+        assert_eq!(title("245\t00\t$c/ by the Arizona State Land Dept."), None);
+    }
+
+    #[test]
+    fn trims_isbd_punctuation() {
+        assert_eq!(trim_isbd_punctuation("Water resources :"), "Water resources");
+        assert_eq!(trim_isbd_punctuation("a report /"), "a report");
+        assert_eq!(trim_isbd_punctuation("Arizona highways ="), "Arizona highways");
+        assert_eq!(trim_isbd_punctuation("No. 3,"), "No. 3");
+        assert_eq!(trim_isbd_punctuation("Bulletin."), "Bulletin");
+        // the period of an initial stays
+        assert_eq!(trim_isbd_punctuation("Minerals of the U.S. :"), "Minerals of the U.S.");
+        assert_eq!(trim_isbd_punctuation(" :"), "");
+    }
+
+    #[test]
+    fn reads_linking_entries() {
+        let linked = linked_title(&parse_field("780\t00\t$aArizona. Highway Dept.$tArizona highway news.$w(DLC)sn 78001234$w(OCoLC)12345$x0004-1521")).unwrap();
+        assert_eq!(linked.relationship, "Continues");
+        assert_eq!(linked.title, "Arizona highway news");
+        assert_eq!(linked.oclc_number.as_deref(), Some("000012345"));
+        assert_eq!(linked.issn.as_deref(), Some("0004-1521"));
+
+        // the main entry when there's no title
+        let linked = linked_title(&parse_field("785\t07\t$aArizona road news.")).unwrap();
+        assert_eq!(linked.relationship, "Merged to form");
+        assert_eq!(linked.title, "Arizona road news");
+        assert_eq!(linked.oclc_number, None);
+
+        assert_eq!(linked_title(&parse_field("785\t0 \t$tArizona roads")).unwrap().relationship, "Succeeded by");
+        assert_eq!(linked_title(&parse_field("780\t0 \t$tArizona roads")).unwrap().relationship, "Preceded by");
+        assert!(linked_title(&parse_field("785\t00\t$w(OCoLC)12345")).is_none());
+    }
+
+    #[test]
+    fn collapsed_fixed_fields_are_not_used() {
+        let marc = parse(&["008\t\t141024u19uuuuuuazsr 0 0eng d"]);
+        assert!(marc.fixed_field.is_none());
+        assert!(marc.review_notes.iter().any(|note| note.starts_with("The 008 was not used:")));
+        assert!(!marc.review_notes.iter().any(|note| note.contains("government publication")));
+    }
+
+    #[test]
+    fn inverts_surnames_only() {
+        let name = |field: &str| creator(&parse_field(field), false);
+        assert_eq!(name("100\t1 \t$aSmith, John A.,$d1900-1980."), Some("John A. Smith, 1900-1980".to_string()));
+        assert_eq!(name("700\t1 \t$aCaryl, C. R."), Some("C. R. Caryl".to_string()));
+        // forenames and family names
+        assert_eq!(name("100\t0 \t$aJohn Paul$bII,$cPope,$d1920-2005."), Some("John Paul II, Pope, 1920-2005".to_string()));
+        assert_eq!(name("100\t3 \t$aCaryl, family."), Some("Caryl, family".to_string()));
+        // a name with the title of a work stops before the title
+        assert_eq!(name("700\t12\t$aSmith, John,$d1900-1980.$tSelected works."), Some("John Smith, 1900-1980".to_string()));
+        assert_eq!(invert_name("Smith, John, Jr."), "Smith, John, Jr.");
+    }
+
+    #[test]
+    fn joins_corporate_units() {
+        let name = |field: &str| creator(&parse_field(field), false);
+        assert_eq!(
+            name("110\t1 \t$aArizona.$bDept. of Health Services.$bDivision of Licensing,$eissuing body."),
+            Some("Arizona. Dept. of Health Services. Division of Licensing".to_string())
+        );
+        assert_eq!(
+            corporate_units(&parse_field("710\t1 \t$aArizona.$bDept. of Health Services.$tAnnual report.")),
+            ["Arizona", "Dept. of Health Services"]
+        );
+    }
+
+    #[test]
+    fn drops_relator_terms_unless_asked_for() {
+        let field = parse_field("700\t1 \t$aSmith, John,$d1900-1980,$eeditor,$ecompiler.");
+        assert_eq!(creator(&field, false), Some("John Smith, 1900-1980".to_string()));
+        assert_eq!(creator(&field, true), Some("John Smith, 1900-1980, editor, compiler".to_string()));
+        let field = parse_field("710\t2 \t$aArizona Highways (Firm),$4pbl");
+        assert_eq!(creator(&field, false), Some("Arizona Highways (Firm)".to_string()));
+        assert_eq!(creator(&field, true), Some("Arizona Highways (Firm), pbl".to_string()));
+    }
+
+    #[test]
+    fn collapses_duplicate_creators_keeping_the_main_entry_first() {
+        let marc = parse(&[
+            "710\t2 \t$aDesert sunshine exposure tests (Phoenix, Ariz.)",
+            "710\t2 \t$aArizona Highways (Firm)",
+            "110\t2 \t$aDesert Sunshine Exposure Tests (Phoenix, Ariz.),$eauthor.",
+            "710\t2 \t$aArizona highways (Firm).",
+        ]);
+        assert_eq!(
+            marc.creators,
+            ["Desert Sunshine Exposure Tests (Phoenix, Ariz.)", "Arizona Highways (Firm)"]
+        );
+        assert!(marc.review_notes.contains(
+            &"Creator \"Desert sunshine exposure tests (Phoenix, Ariz.)\" was merged into \"Desert Sunshine Exposure Tests (Phoenix, Ariz.)\".".to_string()
+        ));
+        assert!(marc.review_notes.contains(
+            &"Creator \"Arizona highways (Firm)\" was merged into \"Arizona Highways (Firm)\".".to_string()
+        ));
+    }
+
+    #[test]
+    fn sequences_264_publishers_by_first_indicator() {
+        let marc = parse(&[
+            "260\t  \t$aPhoenix, Ariz. :$bArizona Highway Dept.,$c1925-",
+            "264\t31\t$aPhoenix, Ariz. :$bArizona Highways,$c1975-",
+            "264\t 1\t$aPhoenix, Ariz. :$bArizona Dept. of Transportation,$c1967-1969.",
+            "264\t21\t$aTempe, Ariz. :$bArizona State University,$c1970-1974.",
+            "264\t 3\t$aPhoenix, Ariz. :$bJones Press,$c1967-",
+            "264\t 4\t$c©1967",
+        ]);
+        assert_eq!(
+            marc.publisher,
+            "Arizona Dept. of Transportation|Arizona State University|Arizona Highways"
+        );
+        assert_eq!(marc.publisher_for("1968-04"), "Arizona Dept. of Transportation");
+        assert_eq!(marc.publisher_for("1972"), "Arizona State University");
+        assert_eq!(marc.publisher_for("1980-01-15"), "Arizona Highways");
+        // before any statement's years
+        assert_eq!(marc.publisher_for("1950"), marc.publisher);
+    }
+
+    #[test]
+    fn uses_260_without_a_264_publication_statement() {
+        let marc = parse(&[
+            "260\t  \t$aPhoenix, Ariz. :$bDesert Printers :$bArizona Historical Society,$c1967-",
+            "264\t 3\t$aPhoenix, Ariz. :$bJones Press,$c1967-",
+        ]);
+        assert_eq!(marc.publisher, "Arizona Historical Society");
+        assert!(marc.review_notes.contains(
+            &"\"Desert Printers\" in 260 looks like a printer, it was not used as the publisher.".to_string()
+        ));
+    }
+
+    #[test]
+    fn starts_fields_by_tag_and_indicators() {
+        assert!(starts_field("245\t03\t$aAn Arizona desert-ation."));
+        assert!(starts_field("245 03 $aAn Arizona desert-ation."));
+        assert!(starts_field("245 03 ‡a An Arizona desert-ation."));
+        assert!(starts_field("24503‡aAn Arizona desert-ation."));
+        assert!(starts_field("650  0 $aDeserts$zArizona."));
+        assert!(starts_field("008 750101c19679999azuqr p       0   a0eng d"));
+        // wrapped lines that start with a number
+        assert!(!starts_field("100 copies were printed."));
+        assert!(!starts_field("500 copies were printed, $5.00 each."));
+        assert!(!starts_field("1967."));
+    }
+
+    #[test]
+    fn joins_wrapped_lines_that_start_with_a_number() {
+        let marc = parse(&[
+            "245\t00\t$aThe Arizona desert-ation.",
+            "520\t  \t$aA newsletter of the Phoenix chapter, of which",
+            "100 copies were printed.",
+        ]);
+        assert_eq!(marc.summaries, ["A newsletter of the Phoenix chapter, of which 100 copies were printed."]);
+        assert!(marc.creators.is_empty());
+    }
+
+    #[test]
+    fn parses_fields_without_tabs() {
+        let marc = parse(&[
+            "245 00 ‡a The Arizona desert-ation : ‡b the newsletter.",
+            "520    ‡a A newsletter of the Phoenix chapter.",
+            "650  0 $aDeserts$zArizona$vPeriodicals.",
+        ]);
+        assert_eq!(marc.summaries, ["A newsletter of the Phoenix chapter."]);
+        assert!(marc.title.is_some_and(|title| title.starts_with("The Arizona desert-ation")));
+        assert!(!marc.subject_headings.is_empty());
+    }
+
+    /// A binary record of UTF-8 fields, e.g. `("245", b"10\x1faTitle")`.
+    fn iso2709(fields: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut directory = Vec::new();
+        let mut data = Vec::new();
+        for (tag, field) in fields {
+            directory.extend_from_slice(format!("{}{:04}{:05}", tag, field.len() + 1, data.len()).as_bytes());
+            data.extend_from_slice(field);
+            data.push(FIELD_TERMINATOR);
+        }
+        directory.push(FIELD_TERMINATOR);
+        data.push(RECORD_TERMINATOR);
+        let base_address = 24 + directory.len();
+        let mut marc = format!("{:05}nas a22{:05}   4500", base_address + data.len(), base_address).into_bytes();
+        marc.extend(directory);
+        marc.extend(data);
+        marc
+    }
+
+    #[test]
+    fn keeps_delimiter_characters_in_binary_subfields() {
+        let marc = iso2709(&[
+            ("001", b"ocm00012345"),
+            ("245", "00\x1faPrices in $ and \u{2021} :\x1fbUS$5.00 an issue.".as_bytes()),
+        ]);
+        let records = read_marc(&marc).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].indicators(), ('0', '0'));
+        assert_eq!(records[1].subfields.len(), 2);
+        assert_eq!(records[1].subfields[0].value, "Prices in $ and \u{2021} :");
+        let marc = MarcData::try_from_records(records, &Config::default()).unwrap();
+        assert_eq!(marc.title.as_deref(), Some("Prices in $ and \u{2021}: US$5.00 an issue"));
+        assert_eq!(marc.oclc_number.as_deref(), Some("000012345"));
+    }
+
+    #[test]
+    fn rejects_records_shorter_than_a_leader() {
+        let mut marc = b"00024\x1d".to_vec();
+        marc.extend_from_slice(&[b'0'; 20]);
+        marc.push(FIELD_TERMINATOR);
+        assert!(matches!(read_marc(&marc), Err(ArchiveError::InvalidMarc(_))));
+    }
+
+    #[test]
+    fn rejects_directories_that_arent_ascii() {
+        let mut marc = b"00060nas  2200037   4500".to_vec();
+        // `24` then an `é` where the tag's last digit should be
+        marc.extend_from_slice("24é00060000".as_bytes());
+        marc.push(FIELD_TERMINATOR);
+        marc.extend_from_slice(b"00$aTitle");
+        marc.push(FIELD_TERMINATOR);
+        marc.push(RECORD_TERMINATOR);
+        assert!(matches!(read_marc(&marc), Err(ArchiveError::InvalidMarc(_))));
+    }
+}
@@ -0,0 +1,379 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// The character sets MARC-8 can switch to with an escape sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CharacterSet {
+    Ascii,
+    Ansel,
+    BasicGreek,
+    BasicCyrillic,
+    /// `ESC g`, Greek letters used as symbols, e.g. `α` in a chemical name.
+    GreekSymbols,
+    /// `ESC b`
+    Subscripts,
+    /// `ESC p`
+    Superscripts,
+    /// Hebrew, Arabic, Extended Cyrillic, and East Asian sets, decoded as `U+FFFD`.
+    Unsupported { bytes_per_char: usize },
+}
+
+impl CharacterSet {
+    /// The set named by the final character of an escape sequence.
+    fn from_final(final_char: u8, is_multibyte: bool) -> Self {
+        match (final_char, is_multibyte) {
+            (b'1', true) => CharacterSet::Unsupported { bytes_per_char: 3 },
+            (b'B', _) => CharacterSet::Ascii,
+            (b'E', _) => CharacterSet::Ansel,
+            (b'S', _) => CharacterSet::BasicGreek,
+            (b'N', _) => CharacterSet::BasicCyrillic,
+            _ => CharacterSet::Unsupported { bytes_per_char: 1 },
+        }
+    }
+
+    /// Maps a character in 0x21-0x7E of the set, with `true` for a combining mark.
+    fn map(self, code: u8) -> Option<(char, bool)> {
+        match self {
+            CharacterSet::Ascii => Some((code as char, false)),
+            CharacterSet::Ansel => ansel(code | 0x80),
+            CharacterSet::BasicGreek => basic_greek(code),
+            CharacterSet::BasicCyrillic => basic_cyrillic(code).map(|c| (c, false)),
+            CharacterSet::GreekSymbols =>
+                match code {
+                    0x61 => Some(('\u{03B1}', false)),
+                    0x62 => Some(('\u{03B2}', false)),
+                    0x63 => Some(('\u{03B3}', false)),
+                    _ => None,
+                }
+            CharacterSet::Subscripts => script(code, '\u{2080}', ['\u{208D}', '\u{208E}', '\u{208A}', '\u{208B}']),
+            CharacterSet::Superscripts => {
+                match code {
+                    0x31 => Some(('\u{00B9}', false)),
+                    0x32 => Some(('\u{00B2}', false)),
+                    0x33 => Some(('\u{00B3}', false)),
+                    _ => script(code, '\u{2070}', ['\u{207D}', '\u{207E}', '\u{207A}', '\u{207B}']),
+                }
+            }
+            CharacterSet::Unsupported { .. } => Some(('\u{FFFD}', false)),
+        }
+    }
+}
+
+/// Decodes MARC-8 to NFC UTF-8.
+///
+/// MARC-8 is ASCII (G0) and ANSEL (G1) by default, and escape sequences switch either half to Greek,
+/// Cyrillic, and other sets. Combining diacritics come before the letter they go on in MARC-8, and after
+/// it in Unicode, so they are held until the next letter, e.g. `Tucs` `E2` `on` -> `Tucsón`.
+pub fn decode_marc8(bytes: &[u8]) -> String {
+    let mut decoded = String::new();
+    let mut combining: Vec<char> = Vec::new();
+    let mut g0 = CharacterSet::Ascii;
+    let mut g1 = CharacterSet::Ansel;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        i += 1;
+        let (set, code) = match byte {
+            0x1B => {
+                let (consumed, designation) = escape(&bytes[i..]);
+                i += consumed;
+                match designation {
+                    Some((Half::G0, set)) => g0 = set,
+                    Some((Half::G1, set)) => g1 = set,
+                    None => {}
+                }
+                continue;
+            }
+            // non-sorting markers
+            0x88 | 0x89 => {
+                continue;
+            }
+            0x8D => {
+                decoded.push('\u{200D}');
+                continue;
+            }
+            0x8E => {
+                decoded.push('\u{200C}');
+                continue;
+            }
+            0x21..=0x7E => (g0, byte),
+            0xA1..=0xFE => (g1, byte & 0x7F),
+            // spaces, line breaks, delimiters, and anything unassigned pass through as they are
+            _ => {
+                decoded.push(if byte < 0x80 { byte as char } else { '\u{FFFD}' });
+                decoded.extend(combining.drain(..));
+                continue;
+            }
+        };
+
+        if let CharacterSet::Unsupported { bytes_per_char } = set {
+            i = (i + bytes_per_char - 1).min(bytes.len());
+        }
+        match set.map(code) {
+            Some((c, true)) => combining.push(c),
+            Some((c, false)) => {
+                decoded.push(c);
+                decoded.extend(combining.drain(..));
+            }
+            None => decoded.push('\u{FFFD}'),
+        }
+    }
+    decoded.extend(combining);
+
+    decoded.nfc().collect()
+}
+
+#[derive(Clone, Copy)]
+enum Half {
+    G0,
+    G1,
+}
+
+/// Reads an escape sequence after the `ESC`, returning the bytes it used and the set it designates.
+fn escape(bytes: &[u8]) -> (usize, Option<(Half, CharacterSet)>) {
+    match bytes {
+        // the technique 1 sets, for a few characters at a time
+        [b'g', ..] => (1, Some((Half::G0, CharacterSet::GreekSymbols))),
+        [b'b', ..] => (1, Some((Half::G0, CharacterSet::Subscripts))),
+        [b'p', ..] => (1, Some((Half::G0, CharacterSet::Superscripts))),
+        [b's', ..] => (1, Some((Half::G0, CharacterSet::Ascii))),
+        // multibyte, e.g. `ESC $ 1` or `ESC $ , 1`
+        [b'$', b',' | b'(', final_char, ..] => (3, Some((Half::G0, CharacterSet::from_final(*final_char, true)))),
+        [b'$', b')' | b'-', final_char, ..] => (3, Some((Half::G1, CharacterSet::from_final(*final_char, true)))),
+        [b'$', final_char, ..] => (2, Some((Half::G0, CharacterSet::from_final(*final_char, true)))),
+        // ANSEL is `!E`
+        [b'(' | b',', b'!', final_char, ..] => (3, Some((Half::G0, CharacterSet::from_final(*final_char, false)))),
+        [b')' | b'-', b'!', final_char, ..] => (3, Some((Half::G1, CharacterSet::from_final(*final_char, false)))),
+        [b'(' | b',', final_char, ..] => (2, Some((Half::G0, CharacterSet::from_final(*final_char, false)))),
+        [b')' | b'-', final_char, ..] => (2, Some((Half::G1, CharacterSet::from_final(*final_char, false)))),
+        _ => (0, None),
+    }
+}
+
+/// ANSEL (ANSI/NISO Z39.47), 0xA1-0xFE. 0xE0 and up are combining diacritics.
+fn ansel(code: u8) -> Option<(char, bool)> {
+    let spacing = match code {
+        0xA1 => '\u{0141}', // Ł
+        0xA2 => '\u{00D8}', // Ø
+        0xA3 => '\u{0110}', // Đ
+        0xA4 => '\u{00DE}', // Þ
+        0xA5 => '\u{00C6}', // Æ
+        0xA6 => '\u{0152}', // Œ
+        0xA7 => '\u{02B9}', // soft sign
+        0xA8 => '\u{00B7}', // middle dot
+        0xA9 => '\u{266D}', // flat
+        0xAA => '\u{00AE}', // ®
+        0xAB => '\u{00B1}', // ±
+        0xAC => '\u{01A0}', // Ơ
+        0xAD => '\u{01AF}', // Ư
+        0xAE => '\u{02BC}', // alif
+        0xB0 => '\u{02BB}', // ayn
+        0xB1 => '\u{0142}', // ł
+        0xB2 => '\u{00F8}', // ø
+        0xB3 => '\u{0111}', // đ
+        0xB4 => '\u{00FE}', // þ
+        0xB5 => '\u{00E6}', // æ
+        0xB6 => '\u{0153}', // œ
+        0xB7 => '\u{02BA}', // hard sign
+        0xB8 => '\u{0131}', // ı
+        0xB9 => '\u{00A3}', // £
+        0xBA => '\u{00F0}', // ð
+        0xBC => '\u{01A1}', // ơ
+        0xBD => '\u{01B0}', // ư
+        0xC0 => '\u{00B0}', // °
+        0xC1 => '\u{2113}', // ℓ
+        0xC2 => '\u{2117}', // ℗
+        0xC3 => '\u{00A9}', // ©
+        0xC4 => '\u{266F}', // sharp
+        0xC5 => '\u{00BF}', // ¿
+        0xC6 => '\u{00A1}', // ¡
+        0xC7 => '\u{00DF}', // ß
+        0xC8 => '\u{20AC}', // €
+        _ => {
+            let combining = match code {
+                0xE0 => '\u{0309}', // hook above
+                0xE1 => '\u{0300}', // grave
+                0xE2 => '\u{0301}', // acute
+                0xE3 => '\u{0302}', // circumflex
+                0xE4 => '\u{0303}', // tilde
+                0xE5 => '\u{0304}', // macron
+                0xE6 => '\u{0306}', // breve
+                0xE7 => '\u{0307}', // dot above
+                0xE8 => '\u{0308}', // umlaut
+                0xE9 => '\u{030C}', // caron
+                0xEA => '\u{030A}', // ring
+                0xEB => '\u{FE20}', // ligature, left half
+                0xEC => '\u{FE21}', // ligature, right half
+                0xED => '\u{0315}', // comma above right
+                0xEE => '\u{030B}', // double acute
+                0xEF => '\u{0310}', // candrabindu
+                0xF0 => '\u{0327}', // cedilla
+                0xF1 => '\u{0328}', // ogonek
+                0xF2 => '\u{0323}', // dot below
+                0xF3 => '\u{0324}', // double dot below
+                0xF4 => '\u{0325}', // circle below
+                0xF5 => '\u{0333}', // double underscore
+                0xF6 => '\u{0332}', // underscore
+                0xF7 => '\u{0326}', // comma below
+                0xF8 => '\u{031C}', // left hook
+                0xF9 => '\u{032E}', // breve below
+                0xFA => '\u{FE22}', // double tilde, left half
+                0xFB => '\u{FE23}', // double tilde, right half
+                0xFE => '\u{0313}', // high comma, centered
+                _ => {
+                    return None;
+                }
+            };
+            return Some((combining, true));
+        }
+    };
+    Some((spacing, false))
+}
+
+/// MARC Basic Greek (`ESC ( S`). 0x21-0x27 are combining accents and breathings.
+fn basic_greek(code: u8) -> Option<(char, bool)> {
+    let combining = match code {
+        0x21 => Some('\u{0300}'),
+        0x22 => Some('\u{0301}'),
+        0x23 => Some('\u{0308}'),
+        0x24 => Some('\u{0342}'),
+        0x25 => Some('\u{0313}'),
+        0x26 => Some('\u{0314}'),
+        0x27 => Some('\u{0345}'),
+        _ => None,
+    };
+    if let Some(c) = combining {
+        return Some((c, true));
+    }
+    let c = match code {
+        0x30 => '\u{00AB}',
+        0x31 => '\u{00BB}',
+        0x32 => '\u{201C}',
+        0x33 => '\u{201D}',
+        0x34 => '\u{0374}',
+        0x35 => '\u{0375}',
+        0x3B => '\u{0387}',
+        0x3F => '\u{037E}',
+        0x41 => '\u{0391}',
+        0x42 => '\u{0392}',
+        0x44 => '\u{0393}',
+        0x45 => '\u{0394}',
+        0x46 => '\u{0395}',
+        0x47 => '\u{03DA}',
+        0x48 => '\u{03DC}',
+        0x49 => '\u{0396}',
+        0x4A => '\u{0397}',
+        0x4B => '\u{0398}',
+        0x4C => '\u{0399}',
+        0x4D => '\u{039A}',
+        0x4E => '\u{039B}',
+        0x4F => '\u{039C}',
+        0x50 => '\u{039D}',
+        0x51 => '\u{039E}',
+        0x52 => '\u{039F}',
+        0x53 => '\u{03A0}',
+        0x54 => '\u{03DE}',
+        0x55 => '\u{03A1}',
+        0x56 => '\u{03A3}',
+        0x58 => '\u{03A4}',
+        0x59 => '\u{03A5}',
+        0x5A => '\u{03A6}',
+        0x5B => '\u{03A7}',
+        0x5C => '\u{03A8}',
+        0x5D => '\u{03A9}',
+        0x5E => '\u{03E0}',
+        0x61 => '\u{03B1}',
+        0x62 => '\u{03B2}',
+        0x63 => '\u{03D0}',
+        0x64 => '\u{03B3}',
+        0x65 => '\u{03B4}',
+        0x66 => '\u{03B5}',
+        0x67 => '\u{03DB}',
+        0x68 => '\u{03DD}',
+        0x69 => '\u{03B6}',
+        0x6A => '\u{03B7}',
+        0x6B => '\u{03B8}',
+        0x6C => '\u{03B9}',
+        0x6D => '\u{03BA}',
+        0x6E => '\u{03BB}',
+        0x6F => '\u{03BC}',
+        0x70 => '\u{03BD}',
+        0x71 => '\u{03BE}',
+        0x72 => '\u{03BF}',
+        0x73 => '\u{03C0}',
+        0x74 => '\u{03DF}',
+        0x75 => '\u{03C1}',
+        0x76 => '\u{03C3}',
+        0x77 => '\u{03C2}',
+        0x78 => '\u{03C4}',
+        0x79 => '\u{03C5}',
+        0x7A => '\u{03C6}',
+        0x7B => '\u{03C7}',
+        0x7C => '\u{03C8}',
+        0x7D => '\u{03C9}',
+        0x7E => '\u{03E1}',
+        // digits and punctuation are shared with ASCII
+        0x28..=0x2F | 0x36..=0x3A | 0x3C..=0x3E => code as char,
+        _ => {
+            return None;
+        }
+    };
+    Some((c, false))
+}
+
+/// MARC Basic Cyrillic (`ESC ( N`). 0x21-0x3F are shared with ASCII.
+fn basic_cyrillic(code: u8) -> Option<char> {
+    const LOWER: &str = "юабцдефгхийклмнопярстужвьызшэщчъ";
+    const UPPER: &str = "ЮАБЦДЕФГХИЙКЛМНОПЯРСТУЖВЬЫЗШЭЩЧ";
+    match code {
+        0x21..=0x3F => Some(code as char),
+        0x40..=0x5F => LOWER.chars().nth((code - 0x40) as usize),
+        0x60..=0x7E => UPPER.chars().nth((code - 0x60) as usize),
+        _ => None,
+    }
+}
+
+/// Digits, parentheses, plus, and minus as subscripts or superscripts.
+fn script(code: u8, zero: char, [open, close, plus, minus]: [char; 4]) -> Option<(char, bool)> {
+    let c = match code {
+        0x30..=0x39 => char::from_u32(zero as u32 + (code - 0x30) as u32)?,
+        0x28 => open,
+        0x29 => close,
+        0x2B => plus,
+        0x2D => minus,
+        _ => {
+            return None;
+        }
+    };
+    Some((c, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_ansel_diacritics_after_their_letter() {
+        assert_eq!(decode_marc8(b"Tucs\xE2on"), "Tucsón");
+        assert_eq!(decode_marc8(b"A\xE4no"), "Año");
+    }
+
+    #[test]
+    fn composes_to_nfc() {
+        let decoded = decode_marc8(b"\xE2e");
+        assert_eq!(decoded, "\u{00E9}");
+        assert_eq!(decoded.chars().count(), 1);
+    }
+
+    #[test]
+    fn switches_to_greek_and_cyrillic() {
+        // `ESC ( S` then `ESC ( B` back to ASCII
+        assert_eq!(decode_marc8(b"\x1B(Sabd\x1B(B and"), "αβγ and");
+        // a Greek acute goes on the letter after it
+        assert_eq!(decode_marc8(b"\x1B(S\x22a\x1B(B"), "\u{03AC}");
+        // `ESC ( N`
+        assert_eq!(decode_marc8(b"\x1B(N\x6D\x49\x52\x1B(B, 1967"), "Мир, 1967");
+        // `ESC ) N` puts Cyrillic in G1, beside ASCII
+        assert_eq!(decode_marc8(b"\x1B)N\xED\xC9\xD2 1967"), "Мир 1967");
+    }
+}
@@ -0,0 +1,1043 @@
+use std::{ collections::{ HashMap, HashSet }, fmt::{ self, Display, Formatter }, io::{ Cursor, Read, Seek, SeekFrom }, path::Path, sync::LazyLock };
+
+use flate2::read::ZlibDecoder;
+use regex::Regex;
+use robo_archiver::ArchiveError;
+
+/// The number of pages whose text layer is extracted by [`inspect_pdf`].
+pub const PDF_TEXT_PAGES: usize = 3;
+
+/// How much of the end of the file, and of the cross-reference section, is read to find the trailer.
+const TRAILER_WINDOW: u64 = 64 * 1024;
+
+/// Guards against malformed page trees that reference themselves.
+const MAX_PAGE_TREE_DEPTH: usize = 32;
+
+/// Everything we can learn about a PDF without OCR.
+#[derive(Debug, Default)]
+pub struct PdfInfo {
+    /// The number of pages, from the page tree.
+    pub page_count: usize,
+    /// Entries from the document information dictionary, e.g. `("Title", "Desert-ation, April 1967")`.
+    pub info: Vec<(String, String)>,
+    /// The raw XMP metadata packet, if the document has one.
+    pub xmp: Option<String>,
+    /// The text layer of the first pages, one entry per page.
+    ///
+    /// Scans without a text layer produce empty strings. Text drawn with composite (CID) fonts can't be
+    /// decoded without the font's ToUnicode map, so it's dropped rather than returned as garbage.
+    pub text: Vec<String>,
+    /// `true` if the document has an `/Encrypt` dictionary, in which case info and text are not read.
+    pub is_encrypted: bool,
+}
+
+impl PdfInfo {
+    /// Looks up a document information entry by key, e.g. `Title`.
+    pub fn info_value(&self, key: &str) -> Option<&str> {
+        self.info
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The `dc:title` from the XMP packet, if present.
+    pub fn xmp_title(&self) -> Option<String> {
+        let xmp = self.xmp.as_ref()?;
+        let start = xmp.find("<dc:title")?;
+        let end = start + xmp[start..].find("</dc:title>")?;
+        let title = strip_xml_tags(&xmp[start..end]);
+        if title.is_empty() {
+            None
+        } else {
+            Some(title)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Display for Confidence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Confidence::Low => write!(f, "low"),
+            Confidence::Medium => write!(f, "medium"),
+            Confidence::High => write!(f, "high"),
+        }
+    }
+}
+
+/// A volume and/or issue number found in a PDF, to be confirmed by the operator.
+#[derive(Debug, Clone)]
+pub struct VolumeIssueSuggestion {
+    /// Arabic numeral, e.g. `Vol. XII` -> `12`.
+    pub volume_no: Option<String>,
+    /// Arabic numeral, e.g. `No. iii` -> `3`.
+    pub issue_no: Option<String>,
+    pub confidence: Confidence,
+    /// Where the suggestion came from, e.g. `found "Vol. XII, No. 3" on page 1`.
+    pub note: String,
+}
+
+impl Display for VolumeIssueSuggestion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(volume_no) = &self.volume_no {
+            parts.push(format!("Volume {}", volume_no));
+        }
+        if let Some(issue_no) = &self.issue_no {
+            parts.push(format!("Issue {}", issue_no));
+        }
+        write!(f, "{} ({} confidence: {})", parts.join(", "), self.confidence, self.note)
+    }
+}
+
+/// Reads the page count, document info, XMP, and the text layer of the first [`PDF_TEXT_PAGES`] pages.
+pub fn inspect_pdf<P>(path: P) -> Result<PdfInfo, ArchiveError> where P: AsRef<Path> {
+    let bytes = std::fs::read(path.as_ref())?;
+    let document = PdfDocument::parse(&bytes);
+    if document.objects.is_empty() {
+        return Err(ArchiveError::Pdf(format!("{:?} contains no PDF objects", path.as_ref())));
+    }
+
+    let trailer = read_trailer(&mut Cursor::new(&bytes))?.or_else(|| document.trailer());
+    let is_encrypted = trailer.as_ref().is_some_and(|t| t.get("Encrypt").is_some());
+    let root = trailer
+        .as_ref()
+        .and_then(|t| t.get("Root"))
+        .and_then(|r| document.resolve(r))
+        .or_else(|| document.find_by_type("Catalog"));
+
+    let pages = root
+        .and_then(|root| root.get("Pages"))
+        .and_then(|pages| document.resolve(pages))
+        .map(|pages| {
+            let mut out = Vec::new();
+            document.collect_pages(pages, 0, &mut out);
+            out
+        })
+        .unwrap_or_default();
+
+    let page_count = root
+        .and_then(|root| root.get("Pages"))
+        .and_then(|pages| document.resolve(pages))
+        .and_then(|pages| pages.get("Count"))
+        .and_then(Object::as_usize)
+        .unwrap_or(pages.len());
+
+    let xmp = root
+        .and_then(|root| root.get("Metadata"))
+        .and_then(|metadata| document.stream_data(metadata))
+        .map(|data| String::from_utf8_lossy(&data).to_string())
+        .or_else(|| find_raw_xmp(&bytes));
+
+    if is_encrypted {
+        return Ok(PdfInfo { page_count, xmp, is_encrypted, ..Default::default() });
+    }
+
+    let info = trailer
+        .as_ref()
+        .and_then(|t| t.get("Info"))
+        .and_then(|i| document.resolve(i))
+        .and_then(|i| i.as_dict())
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|(key, value)| {
+                    let value = document.resolve(value)?;
+                    match value {
+                        Object::String(s) => Some((key.clone(), decode_text_string(s))),
+                        _ => None,
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let text = pages
+        .iter()
+        .take(PDF_TEXT_PAGES)
+        .map(|page| {
+            let content = match page.get("Contents") {
+                Some(Object::Array(parts)) =>
+                    parts
+                        .iter()
+                        .filter_map(|part| document.stream_data(part))
+                        .collect::<Vec<_>>()
+                        .join(&b'\n'),
+                Some(contents) => document.stream_data(contents).unwrap_or_default(),
+                None => Vec::new(),
+            };
+            extract_text(&content)
+        })
+        .collect();
+
+    Ok(PdfInfo { page_count, info, xmp, text, is_encrypted })
+}
+
+/// E.g. `Vol. XII, No. 3`, `Volume 12 Number 3`, or `vol 4`.
+static VOLUME_ISSUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\bvol(?:ume)?\.?\s*([ivxlcdm]+|\d+)\b(?:[\s,.;:-]*(?:no|nos|num|number|iss|issue)\.?\s*([ivxlcdm]+|\d+)\b)?"
+    ).unwrap()
+});
+
+/// E.g. `No. 3` or `Issue 12`, without a volume.
+static ISSUE_ONLY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:no|number|issue)\.?\s*(\d+)\b").unwrap()
+});
+
+/// Searches the metadata titles and the text layer for volume and issue numbers,
+/// e.g. `Vol. XII, No. 3` or `Volume 12 Number 3`.
+pub fn suggest_volume_issue(pdf: &PdfInfo) -> Option<VolumeIssueSuggestion> {
+
+    let mut sources: Vec<(String, &str)> = Vec::new();
+    if let Some(title) = pdf.info_value("Title") {
+        sources.push(("the document title".to_string(), title));
+    }
+    let xmp_title = pdf.xmp_title();
+    if let Some(title) = xmp_title.as_deref() {
+        sources.push(("the XMP title".to_string(), title));
+    }
+    for (i, page) in pdf.text.iter().enumerate() {
+        sources.push((format!("page {}", i + 1), page.as_str()));
+    }
+
+    struct Found {
+        index: usize,
+        source: String,
+        volume_no: Option<String>,
+        issue_no: Option<String>,
+        matched: String,
+    }
+
+    let mut found: Vec<Found> = Vec::new();
+    for (index, (source, text)) in sources.iter().enumerate() {
+        for captures in VOLUME_ISSUE.captures_iter(text) {
+            let Some(volume_no) = captures.get(1).and_then(|m| to_arabic(m.as_str())) else {
+                continue;
+            };
+            let issue_no = captures.get(2).and_then(|m| to_arabic(m.as_str()));
+            let matched = captures.get(0).unwrap().as_str().split_whitespace().collect::<Vec<_>>().join(" ");
+            found.push(Found { index, source: source.clone(), volume_no: Some(volume_no), issue_no, matched });
+        }
+    }
+
+    let first_page_index = sources.iter().position(|(s, _)| s == "page 1");
+    if let Some(Found { index, source, volume_no, issue_no, matched }) = found.first() {
+        let disagree = found.iter().any(|other| {
+            &other.volume_no != volume_no ||
+                (other.issue_no.is_some() && issue_no.is_some() && &other.issue_no != issue_no)
+        });
+        let is_early = first_page_index.is_none_or(|first| *index <= first);
+        let confidence = match (issue_no.is_some(), is_early, disagree) {
+            (_, _, true) => Confidence::Low,
+            (true, true, false) => Confidence::High,
+            _ => Confidence::Medium,
+        };
+        let mut note = format!("found \"{}\" in {}", matched, source);
+        if disagree {
+            note.push_str(", but other pages disagree");
+        }
+        return Some(VolumeIssueSuggestion {
+            volume_no: volume_no.clone(),
+            issue_no: issue_no.clone(),
+            confidence,
+            note,
+        });
+    }
+
+    // A lone "No. 3" is easily a page number or a count, so it's only ever a weak suggestion.
+    sources.iter().find_map(|(source, text)| {
+        let captures = ISSUE_ONLY.captures(text)?;
+        Some(VolumeIssueSuggestion {
+            volume_no: None,
+            issue_no: Some(captures.get(1)?.as_str().trim_start_matches('0').to_string()),
+            confidence: Confidence::Low,
+            note: format!("found \"{}\" in {} without a volume", captures.get(0)?.as_str(), source),
+        })
+    })
+}
+
+/// Converts an arabic or roman numeral to an arabic numeral string, e.g. `XII -> 12`.
+fn to_arabic(numeral: &str) -> Option<String> {
+    if numeral.chars().all(|c| c.is_ascii_digit()) {
+        let trimmed = numeral.trim_start_matches('0');
+        return Some(if trimmed.is_empty() { "0".to_string() } else { trimmed.to_string() });
+    }
+    let value = |c: char| -> Option<u32> {
+        Some(match c.to_ascii_uppercase() {
+            'I' => 1,
+            'V' => 5,
+            'X' => 10,
+            'L' => 50,
+            'C' => 100,
+            'D' => 500,
+            'M' => 1000,
+            _ => {
+                return None;
+            }
+        })
+    };
+    let digits = numeral.chars().map(value).collect::<Option<Vec<_>>>()?;
+    let mut total = 0;
+    for (i, digit) in digits.iter().enumerate() {
+        if digits.get(i + 1).is_some_and(|next| next > digit) {
+            total -= *digit as i64;
+        } else {
+            total += *digit as i64;
+        }
+    }
+    // reject things that only look like roman numerals, e.g. "mix" in "Vol. mixed"
+    if total <= 0 || to_roman(total as u32) != numeral.to_ascii_uppercase() {
+        return None;
+    }
+    Some(total.to_string())
+}
+
+fn to_roman(mut n: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut roman = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            roman.push_str(numeral);
+            n -= value;
+        }
+    }
+    roman
+}
+
+fn strip_xml_tags(s: &str) -> String {
+    let mut out = String::new();
+    let mut in_tag = false;
+    for c in s.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+            }
+            '>' => {
+                in_tag = false;
+                out.push(' ');
+            }
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether a PDF has an `/Encrypt` entry in its trailer, i.e. is password-protected.
+///
+/// Only the end of the file and the cross-reference section are read, so the integrity check can run it on every file.
+pub fn is_encrypted<R>(file: &mut R) -> std::io::Result<bool> where R: Read + Seek {
+    Ok(read_trailer(file)?.is_some_and(|trailer| trailer.get("Encrypt").is_some()))
+}
+
+/// The trailer dictionary, or the dictionary of the cross-reference stream that stands in for it.
+///
+/// It's read where the last `startxref` points, or found near the end of the file when that offset is wrong.
+fn read_trailer<R>(file: &mut R) -> std::io::Result<Option<Object>> where R: Read + Seek {
+    let len = file.seek(SeekFrom::End(0))?;
+    let tail = read_window(file, len.saturating_sub(TRAILER_WINDOW))?;
+    let offset = rfind(&tail, b"startxref")
+        .and_then(|at| Lexer::new(&tail, at + b"startxref".len()).parse_object())
+        .and_then(|offset| offset.as_usize())
+        .filter(|offset| (*offset as u64) < len);
+    let at_startxref = match offset {
+        Some(offset) => xref_section_trailer(&read_window(file, offset as u64)?),
+        None => None,
+    };
+    Ok(at_startxref.or_else(|| PdfDocument::parse(&tail).trailer()))
+}
+
+fn read_window<R>(file: &mut R, from: u64) -> std::io::Result<Vec<u8>> where R: Read + Seek {
+    file.seek(SeekFrom::Start(from))?;
+    let mut window = Vec::new();
+    file.take(TRAILER_WINDOW).read_to_end(&mut window)?;
+    Ok(window)
+}
+
+/// The trailer of a cross-reference section, either `xref ... trailer << >>` or a `n g obj << /Type /XRef >>` stream.
+fn xref_section_trailer(section: &[u8]) -> Option<Object> {
+    let mut lexer = Lexer::new(section, 0);
+    lexer.skip_whitespace();
+    let start = if section[lexer.pos..].starts_with(b"xref") {
+        find(section, b"trailer", lexer.pos)? + b"trailer".len()
+    } else {
+        // `obj` comes right after the object and generation numbers
+        find(section, b"obj", lexer.pos).filter(|at| *at < lexer.pos + 24)? + b"obj".len()
+    };
+    match Lexer::new(section, start).parse_object()? {
+        trailer @ Object::Dict(_) => Some(trailer),
+        _ => None,
+    }
+}
+
+fn find_raw_xmp(bytes: &[u8]) -> Option<String> {
+    let start = find(bytes, b"<x:xmpmeta", 0)?;
+    let end = find(bytes, b"</x:xmpmeta>", start)? + b"</x:xmpmeta>".len();
+    Some(String::from_utf8_lossy(&bytes[start..end]).to_string())
+}
+
+/// Decodes a PDF text string, which is either UTF-16BE with a byte order mark or PDFDocEncoding.
+fn decode_text_string(bytes: &[u8]) -> String {
+    if bytes.starts_with(&[0xfe, 0xff]) {
+        let units = bytes[2..]
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        String::from_utf16_lossy(&units)
+    } else {
+        // PDFDocEncoding agrees with Latin-1 for everything we care about.
+        bytes
+            .iter()
+            .map(|b| *b as char)
+            .collect()
+    }
+}
+
+pub(crate) fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from >= haystack.len() {
+        return None;
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| i + from)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+#[derive(Debug, Clone)]
+enum Object {
+    Null,
+    Number(f64),
+    Name(String),
+    String(Vec<u8>),
+    Array(Vec<Object>),
+    Dict(Vec<(String, Object)>),
+    Ref(u32),
+    /// An operator inside a content stream, e.g. `Tj`.
+    Keyword(String),
+}
+
+impl Object {
+    fn get(&self, key: &str) -> Option<&Object> {
+        self.as_dict()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    fn as_dict(&self) -> Option<&Vec<(String, Object)>> {
+        match self {
+            Object::Dict(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    fn as_name(&self) -> Option<&str> {
+        match self {
+            Object::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn as_usize(&self) -> Option<usize> {
+        match self {
+            Object::Number(n) if *n >= 0.0 => Some(*n as usize),
+            _ => None,
+        }
+    }
+}
+
+struct IndirectObject {
+    value: Object,
+    stream: Option<Vec<u8>>,
+}
+
+/// A forgiving, read-only view of the objects in a PDF.
+///
+/// We don't trust the cross-reference table (scanners and "save as" tools get it wrong often enough),
+/// so objects are found by scanning for `n g obj`. Later definitions win, which matches incremental updates.
+struct PdfDocument<'a> {
+    bytes: &'a [u8],
+    objects: HashMap<u32, IndirectObject>,
+}
+
+impl<'a> PdfDocument<'a> {
+    fn parse(bytes: &'a [u8]) -> Self {
+        let mut objects = HashMap::new();
+        let mut pos = 0;
+        while let Some(at) = find(bytes, b"obj", pos) {
+            pos = at + 3;
+            let Some(num) = object_number_before(bytes, at) else {
+                continue;
+            };
+            let mut lexer = Lexer::new(bytes, pos);
+            let Some(value) = lexer.parse_object() else {
+                continue;
+            };
+            pos = lexer.pos;
+            let mut stream = None;
+            lexer.skip_whitespace();
+            if bytes.get(lexer.pos..).is_some_and(|rest| rest.starts_with(b"stream")) {
+                let mut start = lexer.pos + b"stream".len();
+                if bytes[start..].starts_with(b"\r\n") {
+                    start += 2;
+                } else if bytes[start..].starts_with(b"\n") || bytes[start..].starts_with(b"\r") {
+                    start += 1;
+                }
+                // a `/Length` that's missing, indirect, or wrong falls back to the `endstream` search
+                let direct_end = value
+                    .get("Length")
+                    .and_then(Object::as_usize)
+                    .and_then(|length| start.checked_add(length))
+                    .filter(|end| {
+                        *end <= bytes.len() &&
+                            find(&bytes[*end..], b"endstream", 0).is_some_and(|i| i <= 2)
+                    });
+                let end = direct_end
+                    .or_else(|| find(bytes, b"endstream", start))
+                    .unwrap_or(bytes.len());
+                stream = Some(bytes[start..end].to_vec());
+                pos = end;
+            }
+            objects.insert(num, IndirectObject { value, stream });
+        }
+
+        let mut document = PdfDocument { bytes, objects };
+        document.unpack_object_streams();
+        document
+    }
+
+    /// PDF 1.5+ files keep most dictionaries (including pages) compressed inside `/Type /ObjStm` streams.
+    fn unpack_object_streams(&mut self) {
+        let mut unpacked = Vec::new();
+        for object in self.objects.values() {
+            if object.value.get("Type").and_then(Object::as_name) != Some("ObjStm") {
+                continue;
+            }
+            let Some(data) = decode_stream(object) else {
+                continue;
+            };
+            let count = object.value.get("N").and_then(Object::as_usize).unwrap_or(0);
+            let first = object.value.get("First").and_then(Object::as_usize).unwrap_or(0);
+            let mut header = Lexer::new(&data, 0);
+            for _ in 0..count {
+                let (Some(Object::Number(num)), Some(Object::Number(offset))) = (
+                    header.parse_object(),
+                    header.parse_object(),
+                ) else {
+                    break;
+                };
+                let Some(start) = first.checked_add(offset as usize) else {
+                    break;
+                };
+                let mut lexer = Lexer::new(&data, start);
+                if let Some(value) = lexer.parse_object() {
+                    unpacked.push((num as u32, value));
+                }
+            }
+        }
+        for (num, value) in unpacked {
+            self.objects.entry(num).or_insert(IndirectObject { value, stream: None });
+        }
+    }
+
+    fn resolve<'b>(&'b self, object: &'b Object) -> Option<&'b Object> {
+        match object {
+            Object::Ref(num) => self.objects.get(num).map(|o| &o.value),
+            other => Some(other),
+        }
+    }
+
+    fn stream_data(&self, object: &Object) -> Option<Vec<u8>> {
+        match object {
+            Object::Ref(num) => decode_stream(self.objects.get(num)?),
+            _ => None,
+        }
+    }
+
+    fn find_by_type(&self, type_name: &str) -> Option<&Object> {
+        self.objects
+            .values()
+            .map(|o| &o.value)
+            .find(|o| o.get("Type").and_then(Object::as_name) == Some(type_name))
+    }
+
+    /// The last `trailer` dictionary, or the last cross-reference stream's dictionary.
+    fn trailer(&self) -> Option<Object> {
+        if let Some(at) = rfind(self.bytes, b"trailer") {
+            let mut lexer = Lexer::new(self.bytes, at + b"trailer".len());
+            if let Some(trailer @ Object::Dict(_)) = lexer.parse_object() {
+                return Some(trailer);
+            }
+        }
+        self.objects
+            .iter()
+            .filter(|(_, o)| o.value.get("Type").and_then(Object::as_name) == Some("XRef"))
+            .max_by_key(|(num, _)| **num)
+            .map(|(_, o)| o.value.clone())
+    }
+
+    fn collect_pages<'b>(&'b self, node: &'b Object, depth: usize, out: &mut Vec<&'b Object>) {
+        if depth > MAX_PAGE_TREE_DEPTH {
+            return;
+        }
+        match node.get("Type").and_then(Object::as_name) {
+            Some("Page") => out.push(node),
+            _ => {
+                if let Some(Object::Array(kids)) = node.get("Kids") {
+                    let mut seen = HashSet::new();
+                    for kid in kids {
+                        if let Object::Ref(num) = kid {
+                            if !seen.insert(*num) {
+                                continue;
+                            }
+                        }
+                        if let Some(kid) = self.resolve(kid) {
+                            self.collect_pages(kid, depth + 1, out);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reads `n g` backwards from the `obj` keyword at `at`.
+fn object_number_before(bytes: &[u8], at: usize) -> Option<u32> {
+    let is_ws = |b: u8| b" \t\r\n\x0c\0".contains(&b);
+    let mut i = at;
+    let read_digits = |i: &mut usize| -> Option<(usize, usize)> {
+        while *i > 0 && is_ws(bytes[*i - 1]) {
+            *i -= 1;
+        }
+        let end = *i;
+        while *i > 0 && bytes[*i - 1].is_ascii_digit() {
+            *i -= 1;
+        }
+        if *i == end { None } else { Some((*i, end)) }
+    };
+    if at == 0 || !is_ws(bytes[at - 1]) {
+        return None;
+    }
+    read_digits(&mut i)?;
+    let (start, end) = read_digits(&mut i)?;
+    if start > 0 && !is_ws(bytes[start - 1]) {
+        return None;
+    }
+    std::str::from_utf8(&bytes[start..end]).ok()?.parse().ok()
+}
+
+fn decode_stream(object: &IndirectObject) -> Option<Vec<u8>> {
+    let data = object.stream.as_ref()?;
+    let filters = match object.value.get("Filter") {
+        None => Vec::new(),
+        Some(Object::Name(name)) => vec![name.as_str()],
+        Some(Object::Array(names)) => names.iter().filter_map(Object::as_name).collect(),
+        Some(_) => {
+            return None;
+        }
+    };
+    let mut data = data.clone();
+    for filter in filters {
+        match filter {
+            "FlateDecode" | "Fl" => {
+                let mut decoded = Vec::new();
+                // truncated streams still yield whatever was decoded before the error
+                let _ = ZlibDecoder::new(&data[..]).read_to_end(&mut decoded);
+                if decoded.is_empty() {
+                    return None;
+                }
+                data = decoded;
+            }
+            // images and anything exotic aren't useful for text
+            _ => {
+                return None;
+            }
+        }
+    }
+    Some(data)
+}
+
+/// Pulls the shown strings out of a page content stream.
+fn extract_text(content: &[u8]) -> String {
+    let mut text = String::new();
+    let mut operands: Vec<Object> = Vec::new();
+    let mut lexer = Lexer::new(content, 0);
+    let show = |text: &mut String, s: &[u8]| {
+        // two-byte CIDs decode to unreadable control characters, drop them
+        let decoded = decode_text_string(s);
+        let printable = decoded.chars().filter(|c| !c.is_control()).count();
+        if printable * 2 >= decoded.chars().count() {
+            text.extend(decoded.chars().filter(|c| !c.is_control()));
+        }
+    };
+
+    while let Some(object) = lexer.parse_object() {
+        let Object::Keyword(operator) = object else {
+            operands.push(object);
+            continue;
+        };
+        match operator.as_str() {
+            "Tj" => {
+                if let Some(Object::String(s)) = operands.last() {
+                    show(&mut text, s);
+                }
+            }
+            "'" | "\"" => {
+                text.push('\n');
+                if let Some(Object::String(s)) = operands.last() {
+                    show(&mut text, s);
+                }
+            }
+            "TJ" => {
+                if let Some(Object::Array(parts)) = operands.last() {
+                    for part in parts {
+                        match part {
+                            Object::String(s) => show(&mut text, s),
+                            // a large negative kern is a word gap
+                            Object::Number(n) if *n < -200.0 => text.push(' '),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            "Td" | "TD" => {
+                let moves_down = matches!(operands.last(), Some(Object::Number(ty)) if *ty != 0.0);
+                text.push(if moves_down { '\n' } else { ' ' });
+            }
+            "T*" | "ET" => text.push('\n'),
+            "Tm" => text.push(' '),
+            "ID" => lexer.skip_inline_image(),
+            _ => {}
+        }
+        operands.clear();
+    }
+
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+struct Lexer<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(bytes: &'a [u8], pos: usize) -> Self {
+        Lexer { bytes, pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if b" \t\r\n\x0c\0".contains(&b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while self.peek().is_some_and(|b| b != b'\n' && b != b'\r') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn is_delimiter(b: u8) -> bool {
+        b" \t\r\n\x0c\0()<>[]{}/%".contains(&b)
+    }
+
+    fn read_regular(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| !Self::is_delimiter(b)) {
+            self.pos += 1;
+        }
+        &self.bytes[start..self.pos]
+    }
+
+    fn parse_object(&mut self) -> Option<Object> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'<' if self.bytes.get(self.pos + 1) == Some(&b'<') => {
+                self.pos += 2;
+                let mut entries = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        b'>' if self.bytes.get(self.pos + 1) == Some(&b'>') => {
+                            self.pos += 2;
+                            return Some(Object::Dict(entries));
+                        }
+                        b'/' => {
+                            let Object::Name(key) = self.parse_object()? else {
+                                return None;
+                            };
+                            let value = self.parse_object()?;
+                            entries.push((key, value));
+                        }
+                        _ => {
+                            return None;
+                        }
+                    }
+                }
+            }
+            b'<' => {
+                self.pos += 1;
+                let mut hex = Vec::new();
+                while let Some(b) = self.peek() {
+                    self.pos += 1;
+                    if b == b'>' {
+                        break;
+                    }
+                    if b.is_ascii_hexdigit() {
+                        hex.push(b);
+                    }
+                }
+                if hex.len() % 2 == 1 {
+                    hex.push(b'0');
+                }
+                let bytes = hex
+                    .chunks(2)
+                    .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+                    .collect();
+                Some(Object::String(bytes))
+            }
+            b'(' => Some(Object::String(self.parse_literal_string())),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek()? == b']' {
+                        self.pos += 1;
+                        return Some(Object::Array(items));
+                    }
+                    items.push(self.parse_object()?);
+                }
+            }
+            b'/' => {
+                self.pos += 1;
+                let raw = self.read_regular();
+                let mut name = Vec::new();
+                let mut i = 0;
+                while i < raw.len() {
+                    if raw[i] == b'#' && i + 2 < raw.len() {
+                        if let Ok(b) = u8::from_str_radix(&String::from_utf8_lossy(&raw[i + 1..i + 3]), 16) {
+                            name.push(b);
+                            i += 3;
+                            continue;
+                        }
+                    }
+                    name.push(raw[i]);
+                    i += 1;
+                }
+                Some(Object::Name(String::from_utf8_lossy(&name).to_string()))
+            }
+            b'>' | b']' | b')' | b'{' | b'}' => {
+                // stray delimiter, skip it so content streams keep going
+                self.pos += 1;
+                Some(Object::Keyword(String::new()))
+            }
+            _ => {
+                let token = self.read_regular();
+                if token.is_empty() {
+                    self.pos += 1;
+                    return Some(Object::Keyword(String::new()));
+                }
+                let token = String::from_utf8_lossy(token).to_string();
+                if let Ok(number) = token.parse::<f64>() {
+                    // `n g R` is a reference
+                    let save = self.pos;
+                    if let Ok(num) = token.parse::<u32>() {
+                        self.skip_whitespace();
+                        let generation = String::from_utf8_lossy(self.read_regular()).to_string();
+                        self.skip_whitespace();
+                        if
+                            generation.parse::<u16>().is_ok() &&
+                            self.peek() == Some(b'R') &&
+                            self.bytes.get(self.pos + 1).is_none_or(|b| Self::is_delimiter(*b))
+                        {
+                            self.pos += 1;
+                            return Some(Object::Ref(num));
+                        }
+                    }
+                    self.pos = save;
+                    return Some(Object::Number(number));
+                }
+                // booleans are never needed, so they stay keywords
+                Some(if token == "null" { Object::Null } else { Object::Keyword(token) })
+            }
+        }
+    }
+
+    fn parse_literal_string(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        let mut depth = 1;
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'\\' => {
+                    let Some(escaped) = self.peek() else {
+                        break;
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0c),
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + (d - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => {
+                                        break;
+                                    }
+                                }
+                            }
+                            out.push(value as u8);
+                        }
+                        other => out.push(other),
+                    }
+                }
+                b'(' => {
+                    depth += 1;
+                    out.push(b);
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                    out.push(b);
+                }
+                _ => out.push(b),
+            }
+        }
+        out
+    }
+
+    /// Inline image data is binary and ends at the first `EI` surrounded by whitespace.
+    fn skip_inline_image(&mut self) {
+        while self.pos + 2 <= self.bytes.len() {
+            if
+                &self.bytes[self.pos..self.pos + 2] == b"EI" &&
+                self.bytes[self.pos - 1].is_ascii_whitespace() &&
+                self.bytes.get(self.pos + 2).is_none_or(|b| b.is_ascii_whitespace())
+            {
+                self.pos += 2;
+                return;
+            }
+            self.pos += 1;
+        }
+        self.pos = self.bytes.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncated_dictionary_does_not_panic() {
+        let document = PdfDocument::parse(b"%PDF-1.4\n%%EOF\n1 0 obj <</A 1>");
+        assert!(document.objects.is_empty());
+    }
+
+    #[test]
+    fn truncated_inputs_do_not_panic() {
+        let bytes = b"%PDF-1.4\n1 0 obj\n<< /Length 12 >>\nstream\nBT (Vol. 3) Tj ET\nendstream\nendobj\n";
+        for end in 0..bytes.len() {
+            PdfDocument::parse(&bytes[..end]);
+        }
+    }
+
+    #[test]
+    fn huge_object_stream_offsets_do_not_panic() {
+        let objects = "1 18446744073709551615 ";
+        let body = format!(
+            "%PDF-1.5\n2 0 obj\n<< /Type /ObjStm /N 1 /First 18446744073709551615 /Length {} >>\nstream\n{}\nendstream\nendobj\n",
+            objects.len(),
+            objects
+        );
+        let document = PdfDocument::parse(body.as_bytes());
+        assert!(!document.objects.contains_key(&1));
+    }
+
+    /// A PDF whose cross-reference table is at `startxref`, with `trailer` after it.
+    fn with_trailer(body: &str, trailer: &str) -> Vec<u8> {
+        let xref = body.len();
+        format!("{}xref\n0 1\n0000000000 65535 f \ntrailer\n{}\nstartxref\n{}\n%%EOF\n", body, trailer, xref).into_bytes()
+    }
+
+    #[test]
+    fn encrypt_in_a_stream_is_not_encryption() {
+        let body = "%PDF-1.4\n1 0 obj\n<< /Length 14 >>\nstream\n/Encrypt 2 0 R\nendstream\nendobj\n";
+        let bytes = with_trailer(body, "<< /Size 2 /Root 1 0 R >>");
+        assert!(!is_encrypted(&mut Cursor::new(bytes)).unwrap());
+    }
+
+    #[test]
+    fn encrypt_in_the_trailer_is_encryption() {
+        let bytes = with_trailer("%PDF-1.4\n", "<< /Size 2 /Encrypt 2 0 R >>");
+        assert!(is_encrypted(&mut Cursor::new(bytes)).unwrap());
+    }
+
+    #[test]
+    fn trailer_outside_the_tail_is_found_from_startxref() {
+        let body = "%PDF-1.5\n9 0 obj\n<< /Type /XRef /Encrypt 2 0 R /Length 0 >>\nstream\n";
+        let mut bytes = body.as_bytes().to_vec();
+        bytes.extend(std::iter::repeat_n(b'0', TRAILER_WINDOW as usize * 2));
+        bytes.extend(format!("\nendstream\nendobj\nstartxref\n{}\n%%EOF\n", "%PDF-1.5\n".len()).as_bytes());
+        assert!(is_encrypted(&mut Cursor::new(bytes)).unwrap());
+    }
+
+    #[test]
+    fn huge_length_falls_back_to_endstream() {
+        let bytes = b"1 0 obj\n<< /Length 99999999999999999999999 >>\nstream\nabc\nendstream\nendobj\n";
+        let document = PdfDocument::parse(bytes);
+        let stream = document.objects.get(&1).and_then(|object| object.stream.as_deref());
+        assert_eq!(stream, Some(&b"abc\n"[..]));
+    }
+}
@@ -1,6 +1,6 @@
 use robo_archiver::ArchiveError;
 
-use crate::Periodical;
+use crate::{ IssueNo, Periodical };
 
 const DEFAULT_FILE_NAME: &str = "archive.csv";
 
@@ -28,27 +28,27 @@ const HEADERS: [&str; 56] = [
     "Collection",
     "Subcollection",
     "Rights Statement",
-    "State Agency", // 24, skip
-    "State Sub-Agency", // 25, skip
-    "Federal Legislative Branch Agency", // 26, skip
-    "Federal Executive Department", // 27, skip
-    "Federal Executive Department Sub-Agency or Bureau", // 28, skip
-    "Federal Independent Agency", // 29, skip
-    "Federal Board, Commission, or Committee", // 30, skip
-    "Federal Quasi-Official Agency", // 31, skip
-    "Federal Court or Judicial Agency", // 32, skip
-    "City or Town", // 33, skip
-    "Geographic Feature", // 34, skip
-    "Tribal Homeland", // 35, skip
-    "Road", // 36, skip
-    "County", // 37, skip
-    "State", // 38, skip
-    "Country", // 39, skip
-    "Agency", // 40, skip
-    "Event", // 41, skip
+    "State Agency", // 24
+    "State Sub-Agency", // 25
+    "Federal Legislative Branch Agency", // 26
+    "Federal Executive Department", // 27
+    "Federal Executive Department Sub-Agency or Bureau", // 28
+    "Federal Independent Agency", // 29
+    "Federal Board, Commission, or Committee", // 30
+    "Federal Quasi-Official Agency", // 31
+    "Federal Court or Judicial Agency", // 32
+    "City or Town", // 33
+    "Geographic Feature", // 34
+    "Tribal Homeland", // 35
+    "Road", // 36
+    "County", // 37
+    "State", // 38
+    "Country", // 39
+    "Agency", // 40
+    "Event", // 41
     "Oral History", // 42, skip
-    "Person", // 43, skip
-    "Place", // 44, skip
+    "Person", // 43
+    "Place", // 44
     "Topic",
     "Acquisition Note", // 46, skip
     "Call Number",
@@ -78,9 +78,18 @@ impl From<Periodical> for Vec<[String; 56]> {
             let next_issue = issue.next_issue.unwrap_or_else(|| "".to_string()); // 6
             let creator = issue.marc.creators.join("|").to_string(); // 7
             let contributor = issue.contributors.join("|").to_string(); // 8
-            let publisher = issue.marc.publisher; // 9
+            let publisher = issue.marc.publisher_for(
+                issue.data_original.first().map(String::as_str).unwrap_or_default()
+            ); // 9
             let volume = issue.volume_no.unwrap_or_else(|| "".to_string()); // 10
-            let issue_no = issue.issue_no.map(|i| i.to_string()).unwrap_or_else(|| "".to_string()); // 11
+            let issue_no = match issue.issue_no {
+                Some(IssueNo::Season(season)) => {
+                    eprintln!("Seasonal issues are not supported yet, the issue number will be left blank. Verify issue \"{}\".", season);
+                    "".to_string()
+                }
+                Some(issue_no) => issue_no.to_string(),
+                None => "".to_string(),
+            }; // 11
             let description = /* 12 */ match i {
                 0 => periodical.description.clone(),
                 _ => "".to_string(),
@@ -101,14 +110,49 @@ impl From<Periodical> for Vec<[String; 56]> {
             };
             let subcollection = issue.parent_collection.to_string(); // 21
             let rights_statement = issue.rights_statement.to_string(); // 22
+            let government = &issue.marc.government_agencies;
+            let state_agency = government.state_agencies.join("|"); // 24
+            let state_sub_agency = government.state_sub_agencies.join("|"); // 25
+            let federal_legislative = government.federal_legislative.join("|"); // 26
+            let federal_executive_department = government.federal_executive_departments.join("|"); // 27
+            let federal_executive_sub_agency = government.federal_executive_sub_agencies.join("|"); // 28
+            let federal_independent = government.federal_independent.join("|"); // 29
+            let federal_board = government.federal_boards.join("|"); // 30
+            let federal_quasi_official = government.federal_quasi_official.join("|"); // 31
+            let federal_court = government.federal_courts.join("|"); // 32
+            let geography = &issue.marc.geography;
+            let city_or_town = geography.cities.join("|"); // 33
+            let geographic_feature = geography.features.join("|"); // 34
+            let tribal_homeland = geography.tribal_homelands.join("|"); // 35
+            let road = geography.roads.join("|"); // 36
+            let county = geography.counties.join("|"); // 37
+            let state = geography.states.join("|"); // 38
+            let country = geography.countries.join("|"); // 39
+            let agency = issue.marc.agencies.join("|"); // 40
+            let event = issue.marc.events.join("|"); // 41
+            let person = issue.marc.persons.join("|"); // 43
+            let place = geography.places.join("|"); // 44
             let topics = match i {
                 0 => periodical.topics.join("|").to_string(), // 45
                 _ => "".to_string(),
             };
             let call_number = issue.marc.call_number.to_string(); // 47
-            let oclc_number = issue.marc.oclc_number; // 49
+            let oclc_number = issue.marc.oclc_number.unwrap_or_default(); // 49
             let digital_format = issue.digital_format.to_string(); // 51
             let digitizing_institution = issue.digitizing_institution.to_string(); // 53
+            let admin_notes = match i {
+                0 => {
+                    let identifiers = issue.marc.issn
+                        .iter()
+                        .map(|issn| format!("ISSN: {}", issn))
+                        .chain(issue.marc.lccn.iter().map(|lccn| format!("LCCN: {}", lccn)));
+                    identifiers
+                        .chain(issue.marc.review_notes.iter().cloned())
+                        .chain(issue.admin_notes.iter().cloned())
+                        .collect::<Vec<_>>()
+                }
+                _ => issue.admin_notes,
+            }.join("; "); // 56
 
             let record: [String; 56] = [
                 node_title, // 0
@@ -134,27 +178,27 @@ impl From<Periodical> for Vec<[String; 56]> {
                 collection, // 20
                 subcollection, // 21
                 rights_statement, // 22
-                blank!(/* 23 */),
-                blank!(/* 24 */),
-                blank!(/* 25 */),
-                blank!(/* 26 */),
-                blank!(/* 27 */),
-                blank!(/* 28 */),
-                blank!(/* 29 */),
-                blank!(/* 30 */),
-                blank!(/* 31 */),
-                blank!(/* 32 */),
-                blank!(/* 34 */),
-                blank!(/* 35 */),
-                blank!(/* 36 */),
-                blank!(/* 37 */),
-                blank!(/* 38 */),
-                blank!(/* 39 */),
-                blank!(/* 40 */),
-                blank!(/* 41 */),
+                state_agency, // 24
+                state_sub_agency, // 25
+                federal_legislative, // 26
+                federal_executive_department, // 27
+                federal_executive_sub_agency, // 28
+                federal_independent, // 29
+                federal_board, // 30
+                federal_quasi_official, // 31
+                federal_court, // 32
+                city_or_town, // 33
+                geographic_feature, // 34
+                tribal_homeland, // 35
+                road, // 36
+                county, // 37
+                state, // 38
+                country, // 39
+                agency, // 40
+                event, // 41
                 blank!(/* 42 */),
-                blank!(/* 43 */),
-                blank!(/* 44 */),
+                person, // 43
+                place, // 44
                 topics, // 45
                 blank!(/* 46 */),
                 call_number, // 47
@@ -166,7 +210,7 @@ impl From<Periodical> for Vec<[String; 56]> {
                 digitizing_institution, // 53
                 blank!(/* 54 */),
                 blank!(/* 55 */),
-                blank!(/* 56 */),
+                admin_notes, // 56
             ];
 
             records.push(record);
@@ -0,0 +1,268 @@
+/// Whether a file or paste is an HTML page rather than a MARC table.
+pub fn is_html(text: &str) -> bool {
+    let start = text
+        .trim_start_matches('\u{FEFF}')
+        .trim_start()
+        .chars()
+        .take(512)
+        .collect::<String>()
+        .to_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html") || start.contains("<table")
+}
+
+/// Pulls the MARC table out of a saved catalog "staff view" page, as lines of `tag\tind\tsubfields`.
+///
+/// Each table row whose first cell is a tag becomes a line, e.g.
+/// `<tr><td>245</td><td>03</td><td>‡a An Arizona desert-ation.</td></tr>`. When the page has no such
+/// rows, its text is returned a line per block, for [`crate::marc::read_marc`] to find the fields in.
+pub fn staff_view_marc(html: &str) -> String {
+    let rows = rows(html);
+    let lines = rows
+        .iter()
+        .filter_map(|cells| {
+            let start = cells.iter().position(|cell| !cell.is_empty())?;
+            let cells = &cells[start..];
+            if is_tag(&cells[0]) {
+                Some(row_line(cells))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        page_text(html)
+    } else {
+        lines.join("\n")
+    }
+}
+
+/// A row as a line of the MARC table. Some pages give each indicator a cell of its own, e.g.
+/// `<td>245</td><td>0</td><td>3</td><td>‡a ...</td>`, with a blank indicator as an empty cell.
+fn row_line(cells: &[String]) -> String {
+    let (tag, rest) = cells.split_first().expect("a row has at least its tag");
+    if tag.starts_with("00") {
+        return format!("{}\t\t{}", tag, rest.last().map_or("", String::as_str));
+    }
+    let Some(start) = rest.iter().position(|cell| cell.starts_with(['‡', '$'])) else {
+        return cells.join("\t");
+    };
+    let ind = rest[..start]
+        .iter()
+        .map(|cell| if cell.is_empty() { " " } else { cell.as_str() })
+        .collect::<String>();
+    format!("{}\t{}\t{}", tag, ind, rest[start..].join(" "))
+}
+
+fn is_tag(cell: &str) -> bool {
+    cell.len() == 3 && cell.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The cells of every table row, with their text decoded and whitespace collapsed.
+fn rows(html: &str) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut cells: Vec<String> = Vec::new();
+    let mut cell = String::new();
+    for token in tokens(html) {
+        match token {
+            Token::Text(text) => cell.push_str(text),
+            Token::Tag(name) => match name.as_str() {
+                "tr" | "/tr" | "/table" => {
+                    if !cell.trim().is_empty() {
+                        cells.push(collapse_whitespace(&cell));
+                    }
+                    if !cells.is_empty() {
+                        rows.push(std::mem::take(&mut cells));
+                    }
+                    cell.clear();
+                }
+                "/td" | "/th" => {
+                    cells.push(collapse_whitespace(&cell));
+                    cell.clear();
+                }
+                "td" | "th" => cell.clear(),
+                // lines that wrapped in the cell
+                "br" | "p" | "/p" | "div" | "/div" => cell.push(' '),
+                _ => {}
+            },
+        }
+    }
+    rows
+}
+
+/// The text of the page, a line per block.
+fn page_text(html: &str) -> String {
+    let mut text = String::new();
+    for token in tokens(html) {
+        match token {
+            Token::Text(t) => text.push_str(t),
+            Token::Tag(name) => match name.trim_start_matches('/') {
+                "tr" | "br" | "p" | "div" | "li" | "table" | "h1" | "h2" | "h3" => text.push('\n'),
+                "td" | "th" => text.push('\t'),
+                _ => {}
+            },
+        }
+    }
+    text.lines()
+        .map(|line| {
+            line.split('\t').map(collapse_whitespace).collect::<Vec<_>>().join("\t")
+        })
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+enum Token<'a> {
+    /// Text between tags, with the entities still in it.
+    Text(&'a str),
+    /// A lowercase tag name, with a `/` for closing tags, e.g. `td` or `/tr`.
+    Tag(String),
+}
+
+/// Splits a page into text and tags, skipping comments, scripts, and styles.
+fn tokens(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        let name = rest[1..end]
+            .split(|c: char| c.is_whitespace() || c == '>')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_lowercase();
+        rest = &rest[end + 1..];
+        if name == "script" || name == "style" {
+            let close = format!("</{}", name);
+            rest = rest.to_ascii_lowercase().find(&close).map_or("", |end| &rest[end..]);
+            continue;
+        }
+        tokens.push(Token::Tag(name));
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    tokens
+}
+
+/// Decodes entities and collapses runs of whitespace to one space, e.g. `&Dagger;a  Phoenix` -> `‡a Phoenix`.
+fn collapse_whitespace(text: &str) -> String {
+    decode_entities(text)
+        .split(|c: char| c.is_whitespace() || c == '\u{00A0}')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| entity_char(&rest[1..end + 1]).map(|c| (c, end + 2)));
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// The character of an entity name without the `&` and `;`, e.g. `Dagger`, `#8225`, or `#x2021`.
+fn entity_char(name: &str) -> Option<char> {
+    if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        return u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+    }
+    if let Some(decimal) = name.strip_prefix('#') {
+        return decimal.parse::<u32>().ok().and_then(char::from_u32);
+    }
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        "Dagger" => Some('‡'),
+        "dagger" => Some('†'),
+        "copy" => Some('©'),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ config::Config, data::MarcData, marc::read_marc };
+
+    /// A staff view page as a browser saves it, trimmed to the MARC table.
+    const STAFF_VIEW: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <title>Staff view</title>
+  <script>var rows = "<tr><td>999</td></tr>";</script>
+</head>
+<body>
+  <table class="marc">
+    <tr><th>Tag</th><th colspan="2">Ind.</th><th>Subfields</th></tr>
+    <tr><td>001</td><td></td><td></td><td>ocm00012345</td></tr>
+    <tr><td>245</td><td>0</td><td>3</td><td>&Dagger;a An Arizona desert-ation :<br>
+      &Dagger;b the magazine of the
+      <br/>desert.</td></tr>
+    <tr><td>260</td><td>&nbsp;</td><td>&nbsp;</td><td>&#x2021;a Phoenix, Ariz. :&#x2021;b Arizona Highway Dept.</td></tr>
+    <tr><td>650</td><td></td><td>0</td><td>&Dagger;a Deserts &Dagger;z Arizona &Dagger;v Periodicals.</td></tr>
+    <tr><td>500</td><td colspan="2"></td><td>&Dagger;a Price $1.00 &amp; up.</td></tr>
+  </table>
+</body>
+</html>"#;
+
+    #[test]
+    fn reads_a_saved_staff_view() {
+        assert!(is_html(STAFF_VIEW));
+        assert_eq!(
+            staff_view_marc(STAFF_VIEW).lines().collect::<Vec<_>>(),
+            [
+                "001\t\tocm00012345",
+                "245\t03\t‡a An Arizona desert-ation : ‡b the magazine of the desert.",
+                "260\t  \t‡a Phoenix, Ariz. :‡b Arizona Highway Dept.",
+                "650\t 0\t‡a Deserts ‡z Arizona ‡v Periodicals.",
+                "500\t \t‡a Price $1.00 & up.",
+            ]
+        );
+
+        let records = read_marc(STAFF_VIEW.as_bytes()).unwrap();
+        let marc = MarcData::try_from_records(records, &Config::default()).unwrap();
+        assert_eq!(marc.oclc_number.as_deref(), Some("000012345"));
+        assert_eq!(marc.title.as_deref(), Some("An Arizona desert-ation: the magazine of the desert"));
+        assert_eq!(marc.place_of_publication.as_deref(), Some("Phoenix, Ariz."));
+        assert_eq!(marc.subject_headings, ["Deserts--Arizona--Periodicals"]);
+        assert_eq!(marc.general_notes, ["Price $1.00 & up."]);
+    }
+
+    #[test]
+    fn decodes_entities() {
+        assert_eq!(decode_entities("&Dagger;a &#8225;b &#x2021;c"), "‡a ‡b ‡c");
+        assert_eq!(decode_entities("Fish &amp; Game &unknown; & more"), "Fish & Game &unknown; & more");
+    }
+}
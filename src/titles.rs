@@ -0,0 +1,288 @@
+use unicode_normalization::UnicodeNormalization;
+
+use crate::data::{ IssueFileData, LinkedTitle, MarcData };
+
+/// How alike two normalized titles must be to count as the same title, from 0 to 1.
+const SIMILAR_TITLE: f64 = 0.85;
+
+/// Articles dropped from the start of a title, as MARC's nonfiling characters would.
+const LEADING_ARTICLES: [&str; 6] = ["the ", "a ", "an ", "el ", "la ", "los "];
+
+/// Reduces a title to its letters and digits for comparison.
+///
+/// E.g. `An Arizona desert-ation.` and `Arizona_Desertation` -> `arizonadesertation`.
+pub fn normalize_title(title: &str) -> String {
+    let title = title
+        .nfkd()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace() || *c == '_')
+        .collect::<String>()
+        .replace('_', " ")
+        .to_lowercase();
+    let title = title.trim_start();
+    let title = LEADING_ARTICLES.iter()
+        .find_map(|article| title.strip_prefix(article))
+        .unwrap_or(title);
+    title.chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+/// How alike two titles are, from 0 (nothing in common) to 1 (the same once normalized).
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize_title(a), normalize_title(b));
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - (edit_distance(&a, &b) as f64) / (longest as f64)
+}
+
+/// Whether two titles are likely the same, allowing for punctuation, case, and small typos.
+pub fn is_similar_title(a: &str, b: &str) -> bool {
+    title_similarity(a, b) >= SIMILAR_TITLE
+}
+
+/// The issues with the title from the MARC record (245) instead of the one from their file names, for
+/// `--marc-titles`. Unchanged without a MARC title.
+pub fn with_marc_title(issue_datas: &[IssueFileData], marc_title: Option<&str>) -> Vec<IssueFileData> {
+    issue_datas
+        .iter()
+        .cloned()
+        .map(|mut issue_data| {
+            if let Some(title) = marc_title {
+                issue_data.node_title = title.to_string();
+            }
+            issue_data
+        })
+        .collect()
+}
+
+/// A title change between two of the periodicals being processed, by their index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TitleChange {
+    pub earlier: usize,
+    pub later: usize,
+    /// How the earlier title relates to the later, e.g. `Continued by`.
+    pub forward: String,
+    /// How the later title relates to the earlier, e.g. `Continues`.
+    pub backward: String,
+    /// Whether an OCLC number or ISSN matched, rather than only a similar title.
+    pub by_identifier: bool,
+}
+
+/// How a 780 or 785 matched a periodical.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum LinkMatch {
+    Title,
+    Identifier,
+}
+
+/// Whether a linking entry names the periodical with this title and record.
+///
+/// The title is only compared when neither identifier can be, so a linking entry whose OCLC number or ISSN
+/// names some other record isn't matched by a similar title.
+fn link_match(linked: &LinkedTitle, title: &str, marc: &MarcData) -> Option<LinkMatch> {
+    let same = |a: &Option<String>, b: &Option<String>| match (a, b) {
+        (Some(a), Some(b)) => Some(a == b),
+        _ => None,
+    };
+    match (same(&linked.oclc_number, &marc.oclc_number), same(&linked.issn, &marc.issn)) {
+        (Some(true), _) | (_, Some(true)) => { return Some(LinkMatch::Identifier); }
+        (Some(false), _) | (_, Some(false)) => { return None; }
+        _ => {}
+    }
+    is_similar_title(&linked.title, title).then_some(LinkMatch::Title)
+}
+
+/// Pairs up periodicals whose 785 names a later periodical, or whose 780 names an earlier one.
+///
+/// When two periodicals each name the other as the later title, the pair matched by identifier is kept, or
+/// neither when both only matched by title. Identifier matches come first, so they win any conflict.
+pub fn title_changes(periodicals: &[(&str, &MarcData)]) -> Vec<TitleChange> {
+    let mut changes: Vec<TitleChange> = Vec::new();
+    for (i, (earlier_title, earlier)) in periodicals.iter().enumerate() {
+        for (j, (later_title, later)) in periodicals.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let succeeded_by = earlier.succeeding_titles
+                .iter()
+                .filter_map(|linked| link_match(linked, later_title, later).map(|found| (found, linked)))
+                .max_by_key(|(found, _)| *found);
+            let preceded_by = later.preceding_titles
+                .iter()
+                .filter_map(|linked| link_match(linked, earlier_title, earlier).map(|found| (found, linked)))
+                .max_by_key(|(found, _)| *found);
+            if succeeded_by.is_none() && preceded_by.is_none() {
+                continue;
+            }
+            let found = succeeded_by.iter().chain(preceded_by.iter()).map(|(found, _)| *found).max();
+            changes.push(TitleChange {
+                earlier: i,
+                later: j,
+                forward: succeeded_by.map_or_else(|| "Succeeded by".to_string(), |(_, linked)| linked.relationship.clone()),
+                backward: preceded_by.map_or_else(|| "Preceded by".to_string(), |(_, linked)| linked.relationship.clone()),
+                by_identifier: found == Some(LinkMatch::Identifier),
+            });
+        }
+    }
+
+    let reversed = |change: &TitleChange, changes: &[TitleChange]| {
+        changes.iter().find(|other| other.earlier == change.later && other.later == change.earlier).cloned()
+    };
+    let mut kept = Vec::new();
+    for change in &changes {
+        match reversed(change, &changes) {
+            None => kept.push(change.clone()),
+            Some(other) if change.by_identifier && !other.by_identifier => kept.push(change.clone()),
+            Some(other) if change.by_identifier == other.by_identifier && change.earlier < change.later => {
+                eprintln!(
+                    "[WARN] \"{}\" and \"{}\" each name the other as the later title, so neither was linked.",
+                    periodicals[change.earlier].0,
+                    periodicals[change.later].0
+                );
+            }
+            Some(_) => {}
+        }
+    }
+    kept.sort_by_key(|change| !change.by_identifier);
+    kept
+}
+
+/// The Levenshtein distance between two strings, by characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    use crate::{ config::Config, data::DigitalFormat, marc::parse_marc };
+
+    fn record(lines: &[&str]) -> MarcData {
+        parse_marc(lines.join("\n").as_bytes(), &Config::default()).unwrap()
+    }
+
+    fn issue(node_title: &str) -> IssueFileData {
+        IssueFileData {
+            node_title: node_title.to_string(),
+            date_original: "1967-04".to_string(),
+            date_range: "1960s (1960-1969)".to_string(),
+            format: DigitalFormat::PDF,
+            path: PathBuf::new(),
+        }
+    }
+
+    #[test]
+    fn normalizes_titles_for_comparison() {
+        assert_eq!(normalize_title("An Arizona desert-ation."), "arizonadesertation");
+        assert_eq!(normalize_title("Arizona_Desertation"), "arizonadesertation");
+        assert_eq!(normalize_title("The Tucsón times"), "tucsontimes");
+        // only a whole leading word is an article
+        assert_eq!(normalize_title("Anthology"), "anthology");
+    }
+
+    #[test]
+    fn similar_titles_are_at_least_85_percent_alike() {
+        assert_eq!(title_similarity("Arizona highways", "ARIZONA HIGHWAYS."), 1.0);
+        // one edit in 20 characters
+        assert!(is_similar_title("Arizona desert-ation", "Arizona desert-ationz"));
+        // 3 edits in 20 characters is exactly 0.85
+        assert_eq!(title_similarity("abcdefghijklmnopqrst", "abcdefghijklmnopqxyz"), 0.85);
+        assert!(is_similar_title("abcdefghijklmnopqrst", "abcdefghijklmnopqxyz"));
+        // 4 edits in 20 characters is 0.8
+        assert!(!is_similar_title("abcdefghijklmnopqrst", "abcdefghijklmnopwxyz"));
+        assert!(!is_similar_title("Arizona highways", "Arizona roads"));
+        assert!(!is_similar_title("", ""));
+    }
+
+    #[test]
+    fn marc_titles_replace_file_name_titles_only_when_given() {
+        let issues = [issue("An Arizona Desert-ation"), issue("Arizona Desertation")];
+        let titles = |issues: Vec<IssueFileData>| issues.into_iter().map(|issue| issue.node_title).collect::<Vec<_>>();
+        assert_eq!(titles(with_marc_title(&issues, None)), ["An Arizona Desert-ation", "Arizona Desertation"]);
+        assert_eq!(
+            titles(with_marc_title(&issues, Some("An Arizona desert-ation"))),
+            ["An Arizona desert-ation", "An Arizona desert-ation"]
+        );
+    }
+
+    fn pairs(periodicals: &[(&str, &MarcData)]) -> Vec<(usize, usize, bool)> {
+        title_changes(periodicals)
+            .into_iter()
+            .map(|change| (change.earlier, change.later, change.by_identifier))
+            .collect()
+    }
+
+    #[test]
+    fn pairs_titles_from_either_linking_entry() {
+        let earlier = record(&["035\t\t$a(OCoLC)111", "785\t00\t$tArizona road news$w(OCoLC)222"]);
+        let later = record(&["035\t\t$a(OCoLC)222"]);
+        let changes = title_changes(&[("Arizona highway news", &earlier), ("Arizona road news", &later)]);
+        assert_eq!(
+            changes,
+            [
+                TitleChange {
+                    earlier: 0,
+                    later: 1,
+                    forward: "Continued by".to_string(),
+                    backward: "Preceded by".to_string(),
+                    by_identifier: true,
+                },
+            ]
+        );
+
+        let earlier = record(&["022\t\t$a0004-1521"]);
+        let later = record(&["780\t00\t$tArizona highway news$x0004-1521"]);
+        let changes = title_changes(&[("Arizona road news", &later), ("Arizona highway news", &earlier)]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].earlier, changes[0].later), (1, 0));
+        assert_eq!((changes[0].forward.as_str(), changes[0].backward.as_str()), ("Succeeded by", "Continues"));
+    }
+
+    #[test]
+    fn falls_back_to_the_title_only_without_identifiers_to_compare() {
+        let earlier = record(&["785\t00\t$tArizona road news."]);
+        let later = record(&["245\t00\t$aArizona road news."]);
+        assert_eq!(pairs(&[("Arizona highway news", &earlier), ("Arizona Road News", &later)]), [(0, 1, false)]);
+
+        // the 785 names some other record
+        let earlier = record(&["785\t00\t$tArizona road news.$w(OCoLC)999"]);
+        let later = record(&["035\t\t$a(OCoLC)222"]);
+        assert!(pairs(&[("Arizona highway news", &earlier), ("Arizona Road News", &later)]).is_empty());
+    }
+
+    #[test]
+    fn drops_pairs_that_run_both_ways() {
+        // each names the other as its later title, by title alone
+        let a = record(&["785\t00\t$tArizona road news"]);
+        let b = record(&["785\t00\t$tArizona highway news"]);
+        assert!(pairs(&[("Arizona highway news", &a), ("Arizona road news", &b)]).is_empty());
+
+        // the identifier match wins over the title match
+        let a = record(&["035\t\t$a(OCoLC)111", "785\t00\t$tArizona road news$w(OCoLC)222"]);
+        let b = record(&["035\t\t$a(OCoLC)222", "785\t00\t$tArizona highway news"]);
+        assert_eq!(pairs(&[("Arizona highway news", &a), ("Arizona road news", &b)]), [(0, 1, true)]);
+    }
+
+    #[test]
+    fn identifier_matches_come_first() {
+        let a = record(&["785\t00\t$tArizona road news", "785\t00\t$tArizona roads$w(OCoLC)333"]);
+        let b = record(&["035\t\t$a(OCoLC)222"]);
+        let c = record(&["035\t\t$a(OCoLC)333"]);
+        assert_eq!(
+            pairs(&[("Arizona highway news", &a), ("Arizona road news", &b), ("Arizona roads", &c)]),
+            [(0, 2, true), (0, 1, false)]
+        );
+    }
+}
@@ -12,22 +12,27 @@ enum TopicError {
     TooMany(usize),
 }
 
-fn select_topics(parent_collection: &str) -> Result<Vec<String>, TopicError> {
+fn select_topics(parent_collection: &str, defaults: &[String]) -> Result<Vec<String>, TopicError> {
     const MIN_SELECTIONS: usize = 3;
     let multiselected = load_topics();
+    let checked = multiselected
+        .iter()
+        .map(|topic| defaults.contains(topic))
+        .collect::<Vec<_>>();
     let selections = MultiSelect::with_theme(&ColorfulTheme::default())
         .with_prompt(
             format!("Select 3 topics from the vocabulary list for the \"{}\" collection.", parent_collection)
         )
         .items(&multiselected[..])
+        .defaults(&checked)
         .interact()
         .unwrap();
 
     let selected_len = selections.len();
     if selected_len < MIN_SELECTIONS {
-        return Err(TopicError::NotEnough(selected_len));
+        Err(TopicError::NotEnough(selected_len))
     } else if selected_len > MIN_SELECTIONS {
-        return Err(TopicError::TooMany(selected_len));
+        Err(TopicError::TooMany(selected_len))
     } else {
         let topics = selections
             .iter()
@@ -37,9 +42,9 @@ fn select_topics(parent_collection: &str) -> Result<Vec<String>, TopicError> {
     }
 }
 
-pub fn select_topics_with_retries(parent_collection: &str) -> Vec<String> {
+pub fn select_topics_with_retries(parent_collection: &str, defaults: &[String]) -> Vec<String> {
     loop {
-        match select_topics(parent_collection) {
+        match select_topics(parent_collection, defaults) {
             Ok(topics) => {
                 return topics;
             }